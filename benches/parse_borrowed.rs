@@ -0,0 +1,70 @@
+//! Compares [`parse_borrowed`] with [`parse`] on the same arguments, parsed many times over
+//! as a tool reading command lines in a loop would. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use krh_args_parser::{parse, parse_borrowed, ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    verbose: bool,
+    output: Option<String>,
+    files: usize,
+}
+
+impl ArgumentParser for Tool {
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "output" => self.output = Some(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        _short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.verbose = true;
+        Ok(())
+    }
+
+    fn argument(&mut self, _arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files += 1;
+        Ok(false)
+    }
+}
+
+const ROUNDS: u32 = 20_000;
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let args: Vec<String> = ["-v", "--output", "out.txt"]
+        .into_iter()
+        .map(String::from)
+        .chain((0..50).map(|i| format!("src/file{i}.rs")))
+        .collect();
+
+    let borrowed = time(|| {
+        let tool = parse_borrowed::<Tool>(args.iter().map(String::as_str)).unwrap();
+        black_box(tool);
+    });
+    let owned = time(|| {
+        let tool = parse::<Tool>(args.clone().into_iter()).unwrap();
+        black_box(tool);
+    });
+
+    println!("{} arguments", args.len());
+    println!("parse_borrowed: {borrowed:?} per parse");
+    println!("parse:          {owned:?} per parse");
+}
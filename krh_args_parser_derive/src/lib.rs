@@ -0,0 +1,348 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, ExprLit, Fields, GenericArgument, Ident, Lit,
+    LitChar, LitStr, PathArguments, Type,
+};
+
+/// Derive `ArgumentParser` for a struct from `#[arg(...)]` field attributes.
+///
+/// - `#[arg(long)]` or `#[arg(long = "name")]` makes a field a long flag, named after the field
+///   with `_` replaced by `-` unless given.
+/// - `#[arg(short)]` or `#[arg(short = 'n')]` makes a field a short flag, using the first
+///   character of the field name unless given.
+/// - `#[arg(positional)]` fills an `Option<T>` or `Vec<T>` field with positional arguments,
+///   in field order.
+///
+/// `bool` fields are switches, `Option<T>` fields take one value, `Vec<T>` fields collect
+/// every value they are given, and any other `T` is overwritten with its last value.
+/// Values are parsed with `FromStr`. Doc comments on fields become their help text.
+///
+/// A `Vec<T>` positional takes every remaining argument, so no positional may follow it:
+///
+/// ```compile_fail
+/// #[derive(Default, krh_args_parser::ArgumentParser)]
+/// struct Tool {
+///     #[arg(positional)]
+///     inputs: Vec<String>,
+///     #[arg(positional)]
+///     output: Option<String>,
+/// }
+/// ```
+#[proc_macro_derive(ArgumentParser, attributes(arg))]
+pub fn derive_argument_parser(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How a field stores its values.
+enum Kind {
+    Switch,
+    Optional(Type),
+    Repeated(Type),
+    Single(Type),
+}
+
+struct Field {
+    ident: Ident,
+    kind: Kind,
+    long: Option<String>,
+    short: Option<char>,
+    positional: bool,
+    help: String,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input,
+            "ArgumentParser can only be derived for structs",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "ArgumentParser can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut fields = Vec::new();
+    for field in &named.named {
+        if let Some(field) = parse_field(field)? {
+            fields.push(field);
+        }
+    }
+
+    let long_arms = fields.iter().filter_map(|field| {
+        let long = field.long.as_ref()?;
+        let store = store(field, &format!("--{long}"));
+        Some(quote! { #long => { #store Ok(()) } })
+    });
+    let short_arms = fields.iter().filter_map(|field| {
+        let short = field.short?;
+        let store = store(field, &format!("-{short}"));
+        Some(quote! { #short => { #store Ok(()) } })
+    });
+    let positionals: Vec<_> = fields.iter().filter(|field| field.positional).collect();
+    if let Some(index) = positionals
+        .iter()
+        .position(|field| matches!(field.kind, Kind::Repeated(_)))
+    {
+        if let Some(after) = positionals.get(index + 1) {
+            return Err(syn::Error::new_spanned(
+                &after.ident,
+                "positional fields after a `Vec` positional are never filled",
+            ));
+        }
+    }
+    let fill_positionals = positionals.iter().map(|field| {
+        let ident = &field.ident;
+        let flag = format!("<{ident}>");
+        match &field.kind {
+            Kind::Optional(ty) => {
+                let parse = parse_value(ty, &quote!(arg), &flag);
+                quote! {
+                    if self.#ident.is_none() {
+                        self.#ident = Some(#parse);
+                        return Ok(false);
+                    }
+                }
+            }
+            Kind::Repeated(ty) => {
+                let parse = parse_value(ty, &quote!(arg), &flag);
+                quote! {
+                    self.#ident.push(#parse);
+                    Ok(false)
+                }
+            }
+            Kind::Switch | Kind::Single(_) => unreachable!("checked in parse_field"),
+        }
+    });
+    let unexpected_argument = match positionals.last() {
+        Some(Field {
+            kind: Kind::Repeated(_),
+            ..
+        }) => quote! {},
+        _ => quote! { Err(::krh_args_parser::ParseError::Unhandled) },
+    };
+    let about = docs(&input.attrs);
+    let flag_specs = fields
+        .iter()
+        .filter(|field| !field.positional)
+        .map(|field| {
+            let long = match &field.long {
+                Some(long) => quote! { Some(#long) },
+                None => quote! { None },
+            };
+            let short = match field.short {
+                Some(short) => quote! { Some(#short) },
+                None => quote! { None },
+            };
+            let takes_value = !matches!(field.kind, Kind::Switch);
+            let help = &field.help;
+            quote! {
+                ::krh_args_parser::FlagSpec {
+                    long: #long,
+                    short: #short,
+                    takes_value: #takes_value,
+                    help: #help,
+                    default: None,
+                }
+            }
+        });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::krh_args_parser::ArgumentParser for #name #ty_generics #where_clause {
+            fn long(
+                &mut self,
+                long: &str,
+                next: &mut dyn ::krh_args_parser::Next,
+            ) -> Result<(), ::krh_args_parser::ParseError> {
+                let _ = &next;
+                match long {
+                    #(#long_arms)*
+                    _ => Err(::krh_args_parser::ParseError::Unhandled),
+                }
+            }
+
+            fn short(
+                &mut self,
+                short: char,
+                _is_last: bool,
+                next: &mut dyn ::krh_args_parser::Next,
+            ) -> Result<(), ::krh_args_parser::ParseError> {
+                let _ = &next;
+                match short {
+                    #(#short_arms)*
+                    _ => Err(::krh_args_parser::ParseError::Unhandled),
+                }
+            }
+
+            fn argument(
+                &mut self,
+                arg: &str,
+                _next: &mut dyn ::krh_args_parser::Next,
+            ) -> Result<bool, ::krh_args_parser::ParseError> {
+                #(#fill_positionals)*
+                #unexpected_argument
+            }
+
+            fn describe(&self) -> ::krh_args_parser::CommandSpec {
+                ::krh_args_parser::CommandSpec {
+                    name: env!("CARGO_PKG_NAME"),
+                    about: #about,
+                    flags: vec![#(#flag_specs),*],
+                    subcommands: Vec::new(),
+                }
+            }
+        }
+    })
+}
+
+/// Read a field's `#[arg(...)]` attributes and doc comments.
+/// Returns `None` for fields without `#[arg]`, which are left at their default.
+fn parse_field(field: &syn::Field) -> syn::Result<Option<Field>> {
+    let ident = field.ident.clone().expect("named fields have identifiers");
+    let mut long = None;
+    let mut short = None;
+    let mut positional = false;
+    let mut has_arg = false;
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("arg") {
+            continue;
+        }
+
+        has_arg = true;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("long") {
+                long = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<LitStr>()?.value()
+                } else {
+                    ident.to_string().replace('_', "-")
+                });
+                Ok(())
+            } else if meta.path.is_ident("short") {
+                short = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<LitChar>()?.value()
+                } else {
+                    ident.to_string().chars().next().unwrap()
+                });
+                Ok(())
+            } else if meta.path.is_ident("positional") {
+                positional = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `long`, `short` or `positional`"))
+            }
+        })?;
+    }
+
+    if !has_arg {
+        return Ok(None);
+    }
+
+    let kind = kind(&field.ty);
+    if positional && !matches!(kind, Kind::Optional(_) | Kind::Repeated(_)) {
+        return Err(syn::Error::new_spanned(
+            &field.ty,
+            "positional fields must be `Option<T>` or `Vec<T>`",
+        ));
+    }
+    if !positional && long.is_none() && short.is_none() {
+        return Err(syn::Error::new_spanned(
+            &ident,
+            "expected at least one of `long`, `short` or `positional`",
+        ));
+    }
+
+    Ok(Some(Field {
+        ident,
+        kind,
+        long,
+        short,
+        positional,
+        help: docs(&field.attrs),
+    }))
+}
+
+/// The doc comments in `attrs`, joined into one line.
+fn docs(attrs: &[syn::Attribute]) -> String {
+    let mut docs = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("doc") {
+            continue;
+        }
+        if let syn::Meta::NameValue(meta) = &attr.meta {
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(doc), ..
+            }) = &meta.value
+            {
+                docs.push(doc.value().trim().to_owned());
+            }
+        }
+    }
+    docs.join(" ")
+}
+
+fn kind(ty: &Type) -> Kind {
+    if let Type::Path(path) = ty {
+        if let Some(last) = path.path.segments.last() {
+            if last.ident == "bool" {
+                return Kind::Switch;
+            }
+
+            if let PathArguments::AngleBracketed(args) = &last.arguments {
+                if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                    if last.ident == "Option" {
+                        return Kind::Optional(inner.clone());
+                    }
+                    if last.ident == "Vec" {
+                        return Kind::Repeated(inner.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Kind::Single(ty.clone())
+}
+
+/// Code that parses `value` into `ty`, reporting errors against `flag`.
+fn parse_value(ty: &Type, value: &TokenStream2, flag: &str) -> TokenStream2 {
+    quote! {
+        #value.parse::<#ty>().map_err(|e| ::krh_args_parser::ParseError::InvalidValue {
+            flag: Some(#flag.into()),
+            value: (*#value).to_owned(),
+            reason: e.to_string().into(),
+        })?
+    }
+}
+
+/// Code that takes a value from `next` and stores it in the field.
+fn store(field: &Field, flag: &str) -> TokenStream2 {
+    let ident = &field.ident;
+    let value = format_ident!("value");
+    let take = quote! { let #value = next.take()?; };
+    match &field.kind {
+        Kind::Switch => quote! { self.#ident = true; },
+        Kind::Optional(ty) => {
+            let parse = parse_value(ty, &quote!(#value), flag);
+            quote! { #take self.#ident = Some(#parse); }
+        }
+        Kind::Repeated(ty) => {
+            let parse = parse_value(ty, &quote!(#value), flag);
+            quote! { #take self.#ident.push(#parse); }
+        }
+        Kind::Single(ty) => {
+            let parse = parse_value(ty, &quote!(#value), flag);
+            quote! { #take self.#ident = #parse; }
+        }
+    }
+}
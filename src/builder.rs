@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::{ArgumentParser, Next, ParseError};
+
+type FlagHandler<S> = Box<dyn FnMut(&mut S, &mut dyn Next) -> Result<(), ParseError>>;
+type PositionalHandler<S> = Box<dyn FnMut(&mut S, &str) -> Result<(), ParseError>>;
+
+/// Assemble a parser from closures over a state type `S`, without implementing
+/// [`ArgumentParser`] by hand.
+///
+/// ```
+/// # use krh_args_parser::ParserBuilder;
+/// # use std::collections::HashMap;
+/// let parser = ParserBuilder::new(HashMap::new())
+///     .help("usage: tool [-v] [--name NAME]")
+///     .short('v', |state, _next| {
+///         state.insert("verbose", String::from("true"));
+///         Ok(())
+///     })
+///     .long("name", |state, next| {
+///         state.insert("name", next.take()?);
+///         Ok(())
+///     })
+///     .build();
+///
+/// let args = ["-v", "--name", "world"].map(String::from);
+/// let state = parser.parse(args.into_iter()).unwrap();
+/// assert_eq!(state["name"], "world");
+/// ```
+pub struct ParserBuilder<S> {
+    state: S,
+    help: Cow<'static, str>,
+    long_names: Vec<&'static str>,
+    longs: HashMap<&'static str, FlagHandler<S>>,
+    shorts: HashMap<char, FlagHandler<S>>,
+    positional: Option<PositionalHandler<S>>,
+}
+
+impl<S: 'static> ParserBuilder<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            help: Cow::Borrowed(""),
+            long_names: Vec::new(),
+            longs: HashMap::new(),
+            shorts: HashMap::new(),
+            positional: None,
+        }
+    }
+
+    /// The help message printed for `--help`.
+    pub fn help(mut self, help: impl Into<Cow<'static, str>>) -> Self {
+        self.help = help.into();
+        self
+    }
+
+    /// Handle the long flag `--long`. Abbreviations of it are accepted too.
+    pub fn long(
+        mut self,
+        long: &'static str,
+        handler: impl FnMut(&mut S, &mut dyn Next) -> Result<(), ParseError> + 'static,
+    ) -> Self {
+        if self.longs.insert(long, Box::new(handler)).is_none() {
+            self.long_names.push(long);
+        }
+        self
+    }
+
+    /// Handle the short flag `-short`.
+    pub fn short(
+        mut self,
+        short: char,
+        handler: impl FnMut(&mut S, &mut dyn Next) -> Result<(), ParseError> + 'static,
+    ) -> Self {
+        self.shorts.insert(short, Box::new(handler));
+        self
+    }
+
+    /// Handle every positional argument. Without this, positionals are an error.
+    pub fn positional(
+        mut self,
+        handler: impl FnMut(&mut S, &str) -> Result<(), ParseError> + 'static,
+    ) -> Self {
+        self.positional = Some(Box::new(handler));
+        self
+    }
+
+    pub fn build(self) -> BuiltParser<S> {
+        BuiltParser(self)
+    }
+}
+
+impl<S: Default + 'static> Default for ParserBuilder<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+/// A parser made by [`ParserBuilder`].
+///
+/// Use [`parse`](Self::parse) or [`parse_with`](crate::parse_with) rather than the crate's
+/// other `parse` functions, which would start from a [`Default`] parser with no handlers.
+pub struct BuiltParser<S>(ParserBuilder<S>);
+
+impl<S: Default + 'static> Default for BuiltParser<S> {
+    fn default() -> Self {
+        Self(ParserBuilder::default())
+    }
+}
+
+impl<S: Default + 'static> BuiltParser<S> {
+    /// Parse the given arguments, which should not include the program name, and return
+    /// the final state.
+    pub fn parse(self, args: impl Iterator<Item = String> + 'static) -> Result<S, ParseError> {
+        let parser = crate::parse_with(self, args)?;
+        Ok(parser.0.state)
+    }
+}
+
+impl<S: Default + 'static> ArgumentParser for BuiltParser<S> {
+    fn help(&self) -> Cow<'static, str> {
+        self.0.help.clone()
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        let builder = &mut self.0;
+        match builder.longs.get_mut(long) {
+            Some(handler) => handler(&mut builder.state, next),
+            None => Err(ParseError::Unhandled),
+        }
+    }
+
+    fn known_longs(&self) -> &[&'static str] {
+        &self.0.long_names
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        let builder = &mut self.0;
+        match builder.shorts.get_mut(&short) {
+            Some(handler) => handler(&mut builder.state, next),
+            None => Err(ParseError::Unhandled),
+        }
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        let builder = &mut self.0;
+        match &mut builder.positional {
+            Some(handler) => handler(&mut builder.state, arg).map(|()| false),
+            None => Err(format!("Unexpected argument '{arg}'").into()),
+        }
+    }
+
+    fn subcommand(
+        &mut self,
+        command: &str,
+        _command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<(), ParseError> {
+        Err(format!("Unexpected subcommand '{command}'").into())
+    }
+}
@@ -0,0 +1,256 @@
+//! Shell completion scripts generated from a [`CommandSpec`].
+//!
+//! For example, a `completions` subcommand could print
+//! `completions::bash(&Tool::default().describe())` for the user to source.
+//! Values of flags that take one are completed as file names.
+
+use std::fmt::Write;
+
+use crate::{CommandSpec, FlagSpec};
+
+/// A bash completion script, registered with `complete -F`.
+pub fn bash(spec: &CommandSpec) -> String {
+    let commands = commands(spec);
+    let mut out = String::new();
+
+    let function = format!("_{}", identifier(&[spec.name]));
+    writeln!(out, "{function}() {{").unwrap();
+    out.push_str(
+        "    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+    );
+    writeln!(out, "    local command={} i", identifier(&[spec.name])).unwrap();
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"$command:${COMP_WORDS[i]}\" in\n");
+    for (path, command) in &commands {
+        let id = identifier(path);
+        for sub in &command.subcommands {
+            let sub_id = format!("{id}__{}", identifier(&[sub.name]));
+            writeln!(out, "            {id}:{}) command={sub_id} ;;", sub.name).unwrap();
+        }
+    }
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    out.push_str("    case \"$command\" in\n");
+    for (path, command) in &commands {
+        writeln!(out, "        {})", identifier(path)).unwrap();
+        let values: Vec<_> = command
+            .flags
+            .iter()
+            .filter(|flag| flag.takes_value)
+            .flat_map(FlagSpec::names)
+            .collect();
+        if !values.is_empty() {
+            out.push_str("            case \"$prev\" in\n");
+            writeln!(
+                out,
+                "                {}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;",
+                values.join("|")
+            )
+            .unwrap();
+            out.push_str("            esac\n");
+        }
+        let words: Vec<_> = command
+            .flags
+            .iter()
+            .flat_map(FlagSpec::names)
+            .chain(command.subcommands.iter().map(|sub| sub.name.to_owned()))
+            .collect();
+        writeln!(
+            out,
+            "            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;",
+            words.join(" ")
+        )
+        .unwrap();
+    }
+    out.push_str("    esac\n");
+    out.push_str("}\n\n");
+
+    writeln!(out, "complete -F {function} {}", spec.name).unwrap();
+    out
+}
+
+/// A zsh completion script, for a file named `_<name>` on `$fpath`.
+pub fn zsh(spec: &CommandSpec) -> String {
+    let mut out = format!("#compdef {}\n", spec.name);
+
+    for (path, command) in &commands(spec) {
+        let id = identifier(path);
+        writeln!(out, "\n_{id}() {{").unwrap();
+
+        let mut specs: Vec<_> = command.flags.iter().map(zsh_flag).collect();
+        if command.subcommands.is_empty() {
+            specs.push("'*: :_files'".to_owned());
+            writeln!(
+                out,
+                "    _arguments \\\n        {}",
+                specs.join(" \\\n        ")
+            )
+            .unwrap();
+            out.push_str("}\n");
+            continue;
+        }
+
+        specs.push("'1: :->command'".to_owned());
+        specs.push("'*:: :->args'".to_owned());
+        out.push_str("    local context state state_descr line\n");
+        out.push_str("    typeset -A opt_args\n");
+        writeln!(
+            out,
+            "    _arguments -C \\\n        {}\n",
+            specs.join(" \\\n        ")
+        )
+        .unwrap();
+
+        out.push_str("    case $state in\n");
+        out.push_str("        command)\n");
+        out.push_str("            local commands=(\n");
+        for sub in &command.subcommands {
+            let entry = format!("{}:{}", sub.name.replace(':', "\\:"), sub.about);
+            writeln!(out, "                {}", quote(&entry)).unwrap();
+        }
+        out.push_str("            )\n");
+        out.push_str("            _describe command commands\n");
+        out.push_str("            ;;\n");
+        out.push_str("        args)\n");
+        out.push_str("            case $words[1] in\n");
+        for sub in &command.subcommands {
+            let sub_id = format!("{id}__{}", identifier(&[sub.name]));
+            writeln!(out, "                {}) _{sub_id} ;;", sub.name).unwrap();
+        }
+        out.push_str("            esac\n");
+        out.push_str("            ;;\n");
+        out.push_str("    esac\n");
+        out.push_str("}\n");
+    }
+
+    writeln!(out, "\n_{} \"$@\"", identifier(&[spec.name])).unwrap();
+    out
+}
+
+/// A fish completion script, made of `complete` commands.
+pub fn fish(spec: &CommandSpec) -> String {
+    let mut out = String::new();
+
+    for (path, command) in commands(spec) {
+        let mut conditions: Vec<_> = path[1..]
+            .iter()
+            .map(|name| format!("__fish_seen_subcommand_from {name}"))
+            .collect();
+        if !command.subcommands.is_empty() {
+            let names: Vec<_> = command.subcommands.iter().map(|sub| sub.name).collect();
+            conditions.push(format!(
+                "not __fish_seen_subcommand_from {}",
+                names.join(" ")
+            ));
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" -n {}", quote(&conditions.join("; and ")))
+        };
+
+        for sub in &command.subcommands {
+            write!(
+                out,
+                "complete -c {} -f{condition} -a {}",
+                spec.name, sub.name
+            )
+            .unwrap();
+            if !sub.about.is_empty() {
+                write!(out, " -d {}", fish_quote(sub.about)).unwrap();
+            }
+            out.push('\n');
+        }
+        for flag in &command.flags {
+            write!(out, "complete -c {}{condition}", spec.name).unwrap();
+            if let Some(short) = flag.short {
+                write!(out, " -s {short}").unwrap();
+            }
+            if let Some(long) = flag.long {
+                write!(out, " -l {long}").unwrap();
+            }
+            if flag.takes_value {
+                out.push_str(" -r");
+            }
+            if !flag.help.is_empty() {
+                write!(out, " -d {}", fish_quote(flag.help)).unwrap();
+            }
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Every command in `spec` with its path of names, parents before their subcommands.
+fn commands(spec: &CommandSpec) -> Vec<(Vec<&'static str>, &CommandSpec)> {
+    fn walk<'a>(
+        path: Vec<&'static str>,
+        command: &'a CommandSpec,
+        out: &mut Vec<(Vec<&'static str>, &'a CommandSpec)>,
+    ) {
+        out.push((path.clone(), command));
+        for sub in &command.subcommands {
+            let mut sub_path = path.clone();
+            sub_path.push(sub.name);
+            walk(sub_path, sub, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(vec![spec.name], spec, &mut out);
+    out
+}
+
+/// A shell identifier for a command path, like `tool__build`.
+fn identifier(path: &[&str]) -> String {
+    path.iter()
+        .map(|name| name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+        .collect::<Vec<_>>()
+        .join("__")
+}
+
+/// The `_arguments` spec for a flag.
+fn zsh_flag(flag: &FlagSpec) -> String {
+    let help = if flag.help.is_empty() {
+        String::new()
+    } else {
+        let help = flag.help.replace('\\', "\\\\");
+        format!("[{}]", help.replace('[', "\\[").replace(']', "\\]"))
+    };
+    let value = if flag.takes_value {
+        ":value:_files"
+    } else {
+        ""
+    };
+    let short = flag
+        .short
+        .map(|short| format!("-{short}{}", if flag.takes_value { "+" } else { "" }));
+    let long = flag
+        .long
+        .map(|long| format!("--{long}{}", if flag.takes_value { "=" } else { "" }));
+
+    match (short, long) {
+        (Some(short), Some(long)) => {
+            let names: Vec<_> = flag.names().collect();
+            format!(
+                "{}{{{short},{long}}}{}",
+                quote(&format!("({})", names.join(" "))),
+                quote(&format!("{help}{value}"))
+            )
+        }
+        (Some(name), None) | (None, Some(name)) => quote(&format!("{name}{help}{value}")),
+        (None, None) => String::new(),
+    }
+}
+
+/// Single-quote `s` for a POSIX shell.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Single-quote `s` for fish, which escapes quotes with a backslash.
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -0,0 +1,186 @@
+use std::marker::PhantomData;
+
+use serde::de::value::MapDeserializer;
+use serde::de::{DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+use crate::{ArgumentParser, Next, ParseError};
+
+/// A parser for any type that implements [`Deserialize`](serde::Deserialize), with a long
+/// flag for each field. Flags are gathered into a JSON object and deserialized once parsing
+/// is done:
+/// - `--key value` and `--key=value` set `key` to the string `value`.
+/// - `--key` with no value following sets `key` to `true`.
+/// - A value of `true` or `false` is a boolean.
+/// - A flag given more than once is a list of its values. A list field given once has just
+///   that value.
+/// - Dashes in names become underscores, so `--dry-run` sets `dry_run`.
+///
+/// ```
+/// # use krh_args_parser::{parse_slice, Deserialized};
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     name: String,
+///     dry_run: bool,
+///     #[serde(default)]
+///     tags: Vec<String>,
+/// }
+///
+/// let args = ["--name", "app", "--dry-run", "--tags", "a", "--tags=b"];
+/// let config = parse_slice::<Deserialized<Config>>(&args).unwrap().into_inner().unwrap();
+/// assert_eq!(config.name, "app");
+/// assert!(config.dry_run);
+/// assert_eq!(config.tags, ["a", "b"]);
+///
+/// let args = ["--name", "app", "--dry-run", "--tags", "a"];
+/// let config = parse_slice::<Deserialized<Config>>(&args).unwrap().into_inner().unwrap();
+/// assert_eq!(config.tags, ["a"]);
+///
+/// let error = parse_slice::<Deserialized<Config>>(&["--dry-run"]).err().unwrap();
+/// assert_eq!(error.to_string(), "Invalid arguments: missing field `name`");
+/// ```
+///
+/// There is no schema to go by, so:
+/// - Values are strings, so a number field needs to parse one itself, as with
+///   `#[serde(deserialize_with = ...)]`.
+/// - A switch needs to be last, be followed by another flag, or be given as `--flag=true`,
+///   or it takes the next argument as its value.
+/// - A value cannot start with `-`, even as `--key=-value`.
+/// - Only unit enum variants work, named by their value like `--color never`.
+/// - Nested structs, short flags and positional arguments are not supported.
+pub struct Deserialized<T> {
+    values: Map<String, Value>,
+    parsed: Option<T>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Deserialized<T> {
+    fn default() -> Self {
+        Self {
+            values: Map::new(),
+            parsed: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Deserialized<T> {
+    /// The deserialized value, or `None` if parsing did not finish.
+    pub fn into_inner(self) -> Option<T> {
+        self.parsed
+    }
+}
+
+impl<T: DeserializeOwned> ArgumentParser for Deserialized<T> {
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        let is_value = next.peek().is_some_and(|value| !value.starts_with('-'));
+        let value = if is_value {
+            match next.take()?.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                value => Value::String(value.into()),
+            }
+        } else {
+            Value::Bool(true)
+        };
+
+        match self.values.entry(long.replace('-', "_")) {
+            serde_json::map::Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            serde_json::map::Entry::Occupied(mut entry) => match entry.get_mut() {
+                Value::Array(values) => values.push(value),
+                first => *first = Value::Array(vec![first.take(), value]),
+            },
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        _short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        Err(ParseError::Unhandled)
+    }
+
+    fn argument(&mut self, _arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        Err(ParseError::Unhandled)
+    }
+
+    fn allow_no_args(&self) -> bool {
+        true
+    }
+
+    fn finish(&mut self) -> Result<(), ParseError> {
+        let values = Value::Object(std::mem::take(&mut self.values));
+        let parsed = T::deserialize(Lenient(values))
+            .map_err(|e| ParseError::UserError(format!("Invalid arguments: {e}").into()))?;
+        self.parsed = Some(parsed);
+        Ok(())
+    }
+}
+
+/// A gathered value, which is a list of one where a sequence is wanted, as a flag given
+/// once only has its value.
+struct Lenient(Value);
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Lenient {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Object(fields) => {
+                let fields = fields.into_iter().map(|(key, value)| (key, Lenient(value)));
+                visitor.visit_map(MapDeserializer::new(fields))
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(values) => Value::Array(values).deserialize_seq(visitor),
+            value => Value::Array(vec![value]).deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Lenient(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
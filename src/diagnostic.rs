@@ -0,0 +1,61 @@
+use crate::ParseError;
+
+/// Describe `err` for the user, echoing the command line with a caret under the argument
+/// that caused it, or under just its value for errors about a value like `--flag=val`.
+///
+/// `args` are the arguments as given to the parse, without the program name. Errors that
+/// are not tied to one of them are shown without the command line.
+///
+/// ```
+/// # use krh_args_parser::{parse_slice, render_diagnostic, ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// struct Tool;
+///
+/// impl ArgumentParser for Tool {
+///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+///         next.take()?;
+///         Ok(())
+///     }
+/// }
+///
+/// let args = ["--name=world", "--output"].map(String::from);
+/// let error = parse_slice::<Tool>(&["--name=world", "--output"]).err().unwrap();
+/// assert_eq!(
+///     render_diagnostic(&args, &error),
+///     "\
+/// error: Flag '--output' expected a value but none was given
+///   --name=world --output
+///                ^^^^^^^^"
+/// );
+/// ```
+pub fn render_diagnostic(args: &[String], err: &ParseError) -> String {
+    let ParseError::At {
+        index,
+        value,
+        error,
+    } = err
+    else {
+        return format!("error: {err}");
+    };
+    let Some(arg) = index.checked_sub(1).and_then(|i| args.get(i)) else {
+        return format!("error: {error}");
+    };
+
+    let (start, end) = match value {
+        Some(value) if arg.get(value.clone()).is_some() => (value.start, value.end),
+        _ => (0, arg.len()),
+    };
+    let before: usize = args[..index - 1]
+        .iter()
+        .map(|arg| arg.chars().count() + 1)
+        .sum();
+    let offset = before + arg[..start].chars().count();
+    let width = arg[start..end].chars().count().max(1);
+
+    format!(
+        "error: {error}\n  {}\n  {}{}",
+        args.join(" "),
+        " ".repeat(offset),
+        "^".repeat(width)
+    )
+}
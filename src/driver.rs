@@ -0,0 +1,1340 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::io::Read;
+
+use crate::next::{
+    Args, Attached, Checked, Following, Lookahead, Next, NoValue, ValueOf, Verdicts,
+};
+use crate::token::{classify_with, split_value, Token};
+use crate::{
+    split, use_color, ArgumentParser, DuplicatePolicy, FlagSpec, LongPrefixMode, LongValueMode,
+    ParseError,
+};
+
+/// Log a step of parsing at the trace level, with the `tracing` feature.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)*);
+    };
+}
+
+/// Parse `args` into `parser`.
+///
+/// If `partial` is set, parsing stops at the first argument a handler declines with
+/// [`ParseError::Unhandled`], and that argument and all after it are returned.
+pub(crate) fn run<'a, P: ArgumentParser>(
+    parser: P,
+    args: impl Iterator<Item = impl Into<Cow<'a, OsStr>> + 'a> + 'a,
+    partial: bool,
+) -> Result<(P, Vec<String>), ParseError> {
+    let (parser, result) = run_lenient(parser, args, partial);
+    result.map(|rest| (parser, rest))
+}
+
+/// Like [`run`], but returns the parser even if parsing failed.
+pub(crate) fn run_lenient<'a, P: ArgumentParser>(
+    parser: P,
+    args: impl Iterator<Item = impl Into<Cow<'a, OsStr>> + 'a> + 'a,
+    partial: bool,
+) -> (P, Result<Vec<String>, ParseError>) {
+    let mut args = args.map(Into::into).peekable();
+
+    if args.peek().is_none() && !parser.allow_no_args() {
+        return (parser, Err(ParseError::NoArguments));
+    }
+
+    let args: Box<dyn Iterator<Item = Cow<'a, OsStr>> + 'a> = if parser.allow_response_files() {
+        let mut expanded = Vec::new();
+        if let Err(e) = expand_response_files(args, 0, &mut expanded) {
+            return (parser, Err(e));
+        }
+        Box::new(expanded.into_iter())
+    } else {
+        Box::new(args)
+    };
+
+    let mut driver = Driver::new(parser, Args::new(args), partial);
+    if let Err(e) = driver.apply_config() {
+        return (driver.parser, Err(e));
+    }
+
+    let mut errors = Vec::new();
+    while let Some(arg) = driver.args.next() {
+        driver.taken_count.set(driver.taken_count.get() + 1);
+        let index = driver.taken_count.get();
+        driver.parser.argument_index(index);
+        driver.value_len = None;
+        trace!(index, arg = %arg.to_string_lossy(), "argument");
+
+        let result = driver.token(&arg);
+        let value = driver.value_len.map(|len| {
+            let end = arg.to_string_lossy().len();
+            end - len..end
+        });
+        match result {
+            Ok(false) if driver.parser.should_stop() => {
+                // Stop without finishing, as with `--help`.
+                let rest = if partial {
+                    driver.args.map(lossy).collect()
+                } else {
+                    Vec::new()
+                };
+                return (driver.parser, collected(errors, Ok(())).map(|()| rest));
+            }
+            Ok(false) => {}
+            Ok(true) if !driver.settings.stop_at_first_positional => {
+                let command = driver.subcommand.map_or_else(|| lossy(arg), String::from);
+                driver.deferred = Some(Deferred {
+                    index,
+                    command,
+                    args: Vec::new(),
+                });
+            }
+            Ok(true) => {
+                let Driver {
+                    mut parser,
+                    mut args,
+                    given,
+                    subcommand,
+                    ..
+                } = driver;
+                let command = subcommand.map_or_else(|| arg.to_string_lossy(), Cow::Borrowed);
+                let empty = args.peek().is_none();
+                // The subcommand's iterator outlives borrowed arguments, so they are copied.
+                let args: Vec<String> = args.map(lossy).collect();
+                let result = run_subcommand(&mut parser, &command, empty, args.into_iter())
+                    .map_err(|e| e.at(index, None))
+                    .and_then(|()| finish(&mut parser, given));
+                return (parser, collected(errors, result).map(|()| Vec::new()));
+            }
+            Err(ParseError::Unhandled) if driver.deferred.is_some() => {
+                driver.deferred.as_mut().unwrap().args.push(lossy(arg));
+            }
+            Err(ParseError::Unhandled) if partial => {
+                let Driver {
+                    mut parser,
+                    args,
+                    given,
+                    ..
+                } = driver;
+                let rest = std::iter::once(arg).chain(args).map(lossy).collect();
+                let result = finish(&mut parser, given);
+                return (parser, collected(errors, result).map(|()| rest));
+            }
+            Err(e) if driver.collect_errors && e.is_recoverable() => {
+                errors.push(e.at(index, value))
+            }
+            Err(e) => {
+                let error = collected(errors, Err(e.at(index, value))).unwrap_err();
+                return (driver.parser, Err(error));
+            }
+        }
+    }
+
+    let Driver {
+        mut parser,
+        given,
+        deferred,
+        ..
+    } = driver;
+    let result = match deferred {
+        Some(deferred) => {
+            let empty = deferred.args.is_empty();
+            let args = deferred.args.into_iter();
+            run_subcommand(&mut parser, &deferred.command, empty, args)
+                .map_err(|e| e.at(deferred.index, None))
+        }
+        None => Ok(()),
+    };
+    let result = result.and_then(|()| finish(&mut parser, given));
+    (parser, collected(errors, result).map(|()| Vec::new()))
+}
+
+/// Apply the config file and finish, for a subcommand given no arguments.
+pub(crate) fn run_without_args<P: ArgumentParser>(parser: P) -> Result<P, ParseError> {
+    let mut driver = Driver::new(parser, empty_args(), false);
+    driver.apply_config()?;
+    let Driver {
+        mut parser, given, ..
+    } = driver;
+    finish(&mut parser, given)?;
+    Ok(parser)
+}
+
+/// Call [`ArgumentParser::subcommand_iter`] and then [`ArgumentParser::after_subcommand`],
+/// unless there are no arguments for a command that needs them.
+fn run_subcommand<P: ArgumentParser>(
+    parser: &mut P,
+    command: &str,
+    empty: bool,
+    args: impl Iterator<Item = String> + 'static,
+) -> Result<(), ParseError> {
+    if empty && parser.subcommand_requires_args(command) {
+        return Err(ParseError::EmptySubcommand {
+            command: command.into(),
+        });
+    }
+    trace!(name = command, "subcommand");
+    parser.subcommand_iter(command, args)?;
+    parser.after_subcommand(command)
+}
+
+/// Combine the errors gathered for [`ArgumentParser::collect_errors`] with `result`, from
+/// the last step of parsing.
+fn collected(
+    mut errors: Vec<ParseError>,
+    result: Result<(), ParseError>,
+) -> Result<(), ParseError> {
+    match result {
+        Err(e) if e.is_success() => return Err(e),
+        Err(e) => errors.push(e),
+        Ok(()) => {}
+    }
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(ParseError::Multiple(
+            errors.iter().map(|e| e.to_string().into()).collect(),
+        )),
+    }
+}
+
+/// What was given on the command line, for the checks in [`finish`].
+#[derive(Default)]
+pub(crate) struct Given {
+    /// Every long flag given, after expanding abbreviations and negation.
+    longs: HashSet<String>,
+    /// The number of positional arguments passed to [`ArgumentParser::argument`].
+    arguments: usize,
+    /// The values given for each of [`ArgumentParser::multi_value_flags`], in order.
+    multi: Vec<(&'static str, Vec<String>)>,
+    /// Every long flag set by [`ArgumentParser::config_file`].
+    configured: HashSet<String>,
+}
+
+/// Apply everything that depends on the whole command line, then call
+/// [`ArgumentParser::finish`].
+pub(crate) fn finish<P: ArgumentParser>(parser: &mut P, given: Given) -> Result<(), ParseError> {
+    let Given {
+        longs: seen_longs,
+        arguments,
+        multi,
+        configured,
+    } = given;
+    for (flag, values) in multi {
+        if !values.is_empty() {
+            parser.multi(flag, values)?;
+        }
+    }
+
+    let fallbacks: Vec<_> = parser
+        .env_fallbacks()
+        .iter()
+        .filter(|(long, _)| !seen_longs.contains(*long))
+        .filter_map(|&(long, var)| Some((long, lossy(std::env::var_os(var)?))))
+        .collect();
+    for (long, value) in &fallbacks {
+        let of = ValueOf::Long { prefix: "--", long };
+        let mut next = Attached::new(value, of);
+        validated(parser, &format!("--{long}"), &mut next, |parser, next| {
+            parser.long(long, next)
+        })?;
+    }
+
+    for group in parser.exclusive_groups() {
+        let mut given = group.iter().filter(|long| seen_longs.contains(**long));
+        if let (Some(first), Some(second)) = (given.next(), given.next()) {
+            return Err(ParseError::ExclusiveFlags {
+                first: format!("--{first}"),
+                second: format!("--{second}"),
+            });
+        }
+    }
+
+    let missing = parser.required().iter().find(|long| {
+        !seen_longs.contains(**long)
+            && !configured.contains(**long)
+            && !fallbacks.iter().any(|(given, _)| given == *long)
+    });
+    if let Some(long) = missing {
+        return Err(ParseError::MissingFlag {
+            flag: format!("--{long}"),
+        });
+    }
+
+    let min = parser.min_positionals();
+    if arguments < min {
+        return Err(ParseError::TooFewArguments {
+            min,
+            got: arguments,
+        });
+    }
+
+    parser.finish()
+}
+
+/// Options read from the parser before parsing starts.
+#[derive(Clone, Copy)]
+struct Settings {
+    allow_negative_numbers: bool,
+    auto_help: bool,
+    allow_negation: bool,
+    case_insensitive_longs: bool,
+    allow_plus_flags: bool,
+    allow_slash_flags: bool,
+    max_short_chain: usize,
+    single_dash_longs: bool,
+    positional_keyvals: bool,
+    stop_at_first_positional: bool,
+    passthrough_groups: bool,
+    positional_escape: Option<char>,
+    capture_rest_after: Option<&'static str>,
+    max_positionals: Option<usize>,
+    numeric_short_shortcut: Option<char>,
+    value_separator: char,
+    strict_equals: bool,
+    long_value_mode: LongValueMode,
+}
+
+impl Settings {
+    /// Whether `arg` may be taken as a value rather than being a flag itself.
+    fn is_value(self, arg: &str) -> bool {
+        let plus_flag = self.allow_plus_flags && arg.starts_with('+') && arg.len() > 1;
+        !(arg.starts_with('-') || plus_flag) || self.allow_negative_numbers && is_number(arg)
+    }
+}
+
+/// The state of a single parse.
+struct Driver<'a, P> {
+    parser: P,
+    settings: Settings,
+    /// Whether to carry on past recoverable errors.
+    collect_errors: bool,
+    /// Whether declined arguments end parsing instead of being errors.
+    partial: bool,
+    args: Args<'a>,
+    /// Set once a standalone `--` is seen, after which every argument is positional.
+    options_ended: bool,
+    /// The number of arguments taken so far, including values.
+    taken_count: Cell<usize>,
+    /// Set when an error is about the value attached to the current argument, to the
+    /// value's length. The value is always the end of the argument.
+    value_len: Option<usize>,
+    given: Given,
+    /// Every short flag given, not counting `+` flags.
+    seen_shorts: HashSet<char>,
+    /// The number of positional arguments seen so far, including subcommands.
+    positional_count: usize,
+    /// The full name of a listed subcommand once one is found, which may have been
+    /// abbreviated.
+    subcommand: Option<&'static str>,
+    /// A subcommand found while [`ArgumentParser::stop_at_first_positional`] is off, which
+    /// is handled once the rest of the arguments have been.
+    deferred: Option<Deferred>,
+    /// Set while applying [`ArgumentParser::config_file`], whose values were not spelled
+    /// with `=` and may be left unused by switches.
+    in_config: bool,
+}
+
+/// A subcommand waiting for the end of the arguments.
+struct Deferred {
+    /// The index of the subcommand's name.
+    index: usize,
+    command: String,
+    /// The arguments left for the subcommand.
+    args: Vec<String>,
+}
+
+impl<'a, P: ArgumentParser> Driver<'a, P> {
+    fn new(parser: P, args: Args<'a>, partial: bool) -> Self {
+        let multi = parser
+            .multi_value_flags()
+            .iter()
+            .map(|&flag| (flag, Vec::new()))
+            .collect();
+        Self {
+            settings: Settings {
+                allow_negative_numbers: parser.allow_negative_numbers(),
+                auto_help: parser.auto_help(),
+                allow_negation: parser.allow_negation(),
+                case_insensitive_longs: parser.case_insensitive_longs(),
+                allow_plus_flags: parser.allow_plus_flags(),
+                allow_slash_flags: parser.allow_slash_flags(),
+                max_short_chain: parser.max_short_chain(),
+                single_dash_longs: parser.long_prefix_mode() == LongPrefixMode::SingleDash,
+                positional_keyvals: parser.positional_keyvals(),
+                stop_at_first_positional: parser.stop_at_first_positional(),
+                passthrough_groups: parser.passthrough_groups(),
+                positional_escape: parser.positional_escape(),
+                capture_rest_after: parser.capture_rest_after(),
+                max_positionals: parser.max_positionals(),
+                value_separator: parser.value_separator(),
+                strict_equals: parser.strict_equals(),
+                long_value_mode: parser.long_value_mode(),
+                numeric_short_shortcut: parser.numeric_short_shortcut(),
+            },
+            collect_errors: parser.collect_errors(),
+            partial,
+            parser,
+            args,
+            options_ended: false,
+            taken_count: Cell::new(0),
+            value_len: None,
+            subcommand: None,
+            deferred: None,
+            given: Given {
+                multi,
+                ..Given::default()
+            },
+            seen_shorts: HashSet::new(),
+            positional_count: 0,
+            in_config: false,
+        }
+    }
+
+    /// Pass each entry of [`ArgumentParser::config_file`] on as if it were given as
+    /// `--flag=value`, before any arguments.
+    fn apply_config(&mut self) -> Result<(), ParseError> {
+        let Some(path) = self.parser.config_file() else {
+            return Ok(());
+        };
+        let contents = std::fs::read_to_string(&path).map_err(|e| ParseError::ConfigFile {
+            path: path.display().to_string(),
+            line: None,
+            reason: e.to_string().into(),
+        })?;
+
+        self.in_config = true;
+        let result = contents.lines().enumerate().try_for_each(|(i, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return Ok(());
+            }
+            let entry = line
+                .split_once('=')
+                .map(|(long, value)| (long.trim(), value.trim()))
+                .filter(|(long, _)| !long.is_empty());
+            let Some((long, value)) = entry else {
+                return Err(ParseError::ConfigFile {
+                    path: path.display().to_string(),
+                    line: Some(i + 1),
+                    reason: "Expected 'flag = value'".into(),
+                });
+            };
+            self.long(&format!("--{long}"), "--", long, Some(value))
+                .map_err(|e| ParseError::ConfigEntry {
+                    path: path.display().to_string(),
+                    line: i + 1,
+                    error: Box::new(e),
+                })?;
+            // Flags from the file are not duplicates of the same flags on the command line.
+            self.given.configured.extend(self.given.longs.drain());
+            self.seen_shorts.clear();
+            Ok(())
+        });
+        self.in_config = false;
+        result
+    }
+
+    /// Handle a single argument. Returns whether it starts a subcommand.
+    fn token(&mut self, os_arg: &OsStr) -> Result<bool, ParseError> {
+        let arg = os_arg.to_string_lossy();
+        if self.options_ended {
+            return self.positional(os_arg, true);
+        }
+        if self.deferred.is_none() && self.settings.capture_rest_after == Some(&arg) {
+            self.rest()?;
+            return Ok(false);
+        }
+        let escaped = self
+            .settings
+            .positional_escape
+            .and_then(|escape| arg.strip_prefix(escape));
+        if let Some(arg) = escaped {
+            return self.positional(OsStr::new(arg), true);
+        }
+        let digits = arg
+            .strip_prefix('-')
+            .filter(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()));
+        if let Some((short, digits)) = self.settings.numeric_short_shortcut.zip(digits) {
+            let separator = self.settings.value_separator;
+            self.short(&format!("{short}{separator}{digits}"), false)?;
+            return Ok(false);
+        }
+
+        let separator = self.settings.value_separator;
+        match classify_with(&arg, separator) {
+            Token::Separator => {
+                self.options_ended = true;
+                if let Some(deferred) = &mut self.deferred {
+                    // Everything after the separator belongs to the subcommand, which needs
+                    // to see the separator too.
+                    deferred.args.push(lossy(os_arg));
+                    deferred.args.extend(self.args.by_ref().map(lossy));
+                } else if self.settings.passthrough_groups {
+                    self.passthrough()?;
+                }
+                Ok(false)
+            }
+            Token::Long { name, value } => {
+                self.long(&arg, "--", name, value)?;
+                Ok(false)
+            }
+            Token::Short(s) if !self.settings.is_value(&arg) => {
+                let (name, value) = split_value(s, separator);
+                if self.settings.single_dash_longs && name.chars().nth(1).is_some() {
+                    self.long(&arg, "-", name, value)?;
+                } else {
+                    self.short(s, false)?;
+                }
+                Ok(false)
+            }
+            _ => {
+                if let Some(s) = arg
+                    .strip_prefix('+')
+                    .filter(|s| !s.is_empty() && !self.settings.is_value(&arg))
+                {
+                    self.short(s, true)?;
+                    return Ok(false);
+                }
+
+                let slash = arg.strip_prefix('/').map(|s| split_value(s, ':'));
+                if let Some((name, value)) = slash.filter(|(name, _)| {
+                    // Leave paths like `/path/to/file` as positionals.
+                    self.settings.allow_slash_flags && arg.len() > 1 && !name.contains('/')
+                }) {
+                    self.long(&arg, "/", name, value)?;
+                    return Ok(false);
+                }
+
+                self.positional(os_arg, false)
+            }
+        }
+    }
+
+    /// Handle the long flag `long` given as `arg`, after its `prefix`.
+    fn long(
+        &mut self,
+        arg: &str,
+        prefix: &str,
+        long: &str,
+        value: Option<&str>,
+    ) -> Result<(), ParseError> {
+        match self.dispatch_long(arg, prefix, long, value) {
+            Err(ParseError::Unhandled) => self.parser.unknown_long(arg),
+            result => result,
+        }
+    }
+
+    fn dispatch_long(
+        &mut self,
+        arg: &str,
+        prefix: &str,
+        long: &str,
+        value: Option<&str>,
+    ) -> Result<(), ParseError> {
+        if long.is_empty() {
+            // A bare `--` is the separator, so this is something like `--=value`.
+            return Err(ParseError::EmptyFlag { arg: arg.into() });
+        }
+        let separator = self.settings.value_separator;
+        // How a value was attached only matters on the command line.
+        let attached = value.filter(|_| !self.in_config);
+        if let Some(value) =
+            attached.filter(|value| self.settings.strict_equals && value.starts_with(separator))
+        {
+            self.value_len = Some(value.len());
+            return Err(ParseError::DoubledSeparator {
+                flag: format!("{prefix}{long}"),
+                separator,
+            });
+        }
+        if attached.is_some() && self.settings.long_value_mode == LongValueMode::ForbidEquals {
+            return Err(ParseError::AttachedValueForbidden {
+                flag: format!("{prefix}{long}"),
+                separator,
+            });
+        }
+        let lowercase;
+        let long = if self.settings.case_insensitive_longs {
+            lowercase = long.to_lowercase();
+            &lowercase
+        } else {
+            long
+        };
+        let negated = self.settings.allow_negation && long.starts_with("no-");
+        let long = long.strip_prefix("no-").filter(|_| negated).unwrap_or(long);
+        let long = match resolve_alias(self.parser.aliases(), &format!("--{long}"))? {
+            Some(Flag::Long(long)) => long,
+            Some(Flag::Short(short)) if !negated => {
+                let cluster = match value {
+                    Some(value) => format!("{short}{}{value}", self.settings.value_separator),
+                    None => short.into(),
+                };
+                return self.short(&cluster, false);
+            }
+            _ => long,
+        };
+        let long = expand_long(self.parser.known_longs(), long)?;
+        if !negated && self.parser.stdin_args_flag() == Some(long) {
+            if let Some(value) = value {
+                return Err(self.unexpected_value(long.into(), value));
+            }
+            return self.splice_stdin_args();
+        }
+
+        let deprecated = self
+            .parser
+            .deprecated()
+            .iter()
+            .find(|(old, _)| old.strip_prefix("--") == Some(long));
+        if let Some(&(old, new)) = deprecated {
+            self.parser.warn(deprecation(old, new));
+        }
+
+        let first = self.given.longs.insert(long.into());
+        let multi = self.multi_index(&format!("--{long}"));
+        let handle = multi.is_some() || self.check_duplicate(long, first)?;
+        let allow_dash = self.parser.value_flags().contains(&long);
+
+        if negated {
+            if let Some(value) = attached {
+                return Err(self.unexpected_value(format!("no-{long}"), value));
+            }
+            if !handle {
+                return Ok(());
+            }
+            trace!(name = %format_args!("--no-{long}"), "flag");
+            return self.parser.long_negated(long);
+        }
+
+        match long {
+            "help" if self.settings.auto_help => return Err(show_help(&self.parser)),
+            "version" if self.settings.auto_help => return Err(show_version(&self.parser)),
+            _ => {}
+        }
+
+        if !handle {
+            trace!(name = %format_args!("--{long}"), "duplicate");
+            let following = value.is_none()
+                && self.settings.long_value_mode != LongValueMode::RequireEquals
+                && self.takes_value(|flag| flag.long == Some(long));
+            if following {
+                self.skip_value(allow_dash);
+            }
+            return Ok(());
+        }
+
+        let parser = &mut self.parser;
+
+        let values = multi.map(|i| &mut self.given.multi[i].1);
+        match value {
+            Some(value) => {
+                let mut next = Attached::new(value, ValueOf::Long { prefix, long });
+                let result = long_or_values(parser, long, values, &mut next);
+                if result.is_err() && next.taken {
+                    self.value_len = Some(value.len());
+                }
+                result?;
+
+                if !next.taken && !self.in_config {
+                    return Err(self.unexpected_value(long.into(), value));
+                }
+            }
+            None if self.settings.long_value_mode == LongValueMode::RequireEquals => {
+                let mut next = NoValue(ParseError::AttachedValueRequired {
+                    flag: format!("{prefix}{long}"),
+                    separator: self.settings.value_separator,
+                });
+                long_or_values(parser, long, values, &mut next)?;
+            }
+            None => {
+                let settings = self.settings;
+                let mut next = Following {
+                    args: &mut self.args,
+                    of: ValueOf::Long { prefix, long },
+                    is_value: &|arg| settings.is_value(arg),
+                    taken_count: &self.taken_count,
+                    options_ended: self.options_ended,
+                    allow_dash,
+                };
+                long_or_values(parser, long, values, &mut next)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read arguments for [`ArgumentParser::stdin_args_flag`] and put them before the rest.
+    fn splice_stdin_args(&mut self) -> Result<(), ParseError> {
+        let mut input = String::new();
+        self.parser
+            .stdin_reader()
+            .read_to_string(&mut input)
+            .map_err(|e| ParseError::StdinArgs {
+                reason: e.to_string().into(),
+            })?;
+
+        let stdin_args = split::split(&input)?
+            .into_iter()
+            .map(|arg| Cow::Owned(arg.into()));
+        let rest = std::mem::replace(&mut self.args, empty_args());
+        self.args = Args::new(stdin_args.chain(rest));
+        Ok(())
+    }
+
+    /// Hand the arguments after a `--` to [`ArgumentParser::passthrough`], split into groups
+    /// at each further `--`.
+    fn passthrough(&mut self) -> Result<(), ParseError> {
+        let mut group = Vec::new();
+        let mut index = 0;
+        for arg in self.args.by_ref().map(lossy) {
+            self.taken_count.set(self.taken_count.get() + 1);
+            if arg == "--" {
+                self.parser.passthrough(index, std::mem::take(&mut group))?;
+                index += 1;
+            } else {
+                group.push(arg);
+            }
+        }
+        self.parser.passthrough(index, group)
+    }
+
+    /// Hand every argument left to [`ArgumentParser::rest`].
+    fn rest(&mut self) -> Result<(), ParseError> {
+        trace!("rest");
+        let taken_count = &self.taken_count;
+        let mut args = self.args.by_ref().map(|arg| {
+            taken_count.set(taken_count.get() + 1);
+            lossy(arg)
+        });
+        let result = self.parser.rest(&mut args);
+        args.for_each(drop);
+        result
+    }
+
+    /// The error for a `value` attached to `flag` that went unused.
+    fn unexpected_value(&mut self, flag: String, value: &str) -> ParseError {
+        self.value_len = Some(value.len());
+        ParseError::UnexpectedValue {
+            flag,
+            value: value.into(),
+        }
+    }
+
+    /// The index in [`Given::multi`] of `flag`, spelled like `--long` or `-s`.
+    fn multi_index(&self, flag: &str) -> Option<usize> {
+        self.given
+            .multi
+            .iter()
+            .position(|(multi, _)| *multi == flag)
+    }
+
+    /// Apply [`ArgumentParser::on_duplicate`] to a flag that is not being given for the
+    /// `first` time. Returns whether the flag should be handled.
+    fn check_duplicate(&self, flag: &str, first: bool) -> Result<bool, ParseError> {
+        if first {
+            return Ok(true);
+        }
+        match self.parser.on_duplicate(flag) {
+            DuplicatePolicy::LastWins => Ok(true),
+            DuplicatePolicy::FirstWins => Ok(false),
+            DuplicatePolicy::Error => Err(ParseError::DuplicateFlag { flag: flag.into() }),
+        }
+    }
+
+    /// Whether [`ArgumentParser::describe`] has a flag matching `is_flag` that takes a value,
+    /// for skipping the value of a duplicate that is not handled.
+    fn takes_value(&self, is_flag: impl Fn(&FlagSpec) -> bool) -> bool {
+        let spec = self.parser.describe();
+        spec.flags
+            .iter()
+            .any(|flag| is_flag(flag) && flag.takes_value)
+    }
+
+    /// Skip the following argument if a flag could take it as its value.
+    fn skip_value(&mut self, allow_dash: bool) {
+        let settings = self.settings;
+        let is_value = self.args.peek().is_some_and(|arg| {
+            let arg = arg.to_string_lossy();
+            arg != "--" && (allow_dash || settings.is_value(&arg))
+        });
+        if is_value {
+            self.args.next();
+            self.taken_count.set(self.taken_count.get() + 1);
+        }
+    }
+
+    /// Handle a cluster of short flags, without the leading `-`, or `+` if `plus`.
+    fn short(&mut self, s: &str, plus: bool) -> Result<(), ParseError> {
+        let separator = self.settings.value_separator;
+        if s.starts_with(separator) {
+            // Something like `-=value`, which has no flag for the value to go to.
+            let prefix = if plus { '+' } else { '-' };
+            return Err(ParseError::EmptyFlag {
+                arg: format!("{prefix}{s}"),
+            });
+        }
+
+        let max = self.settings.max_short_chain;
+        let too_long = |len| (len > max).then_some(ParseError::ShortChainTooLong { max });
+
+        let mut len = 0;
+        let mut chars = s.chars();
+        while let Some(given) = chars.next() {
+            len += 1;
+            if let Some(error) = too_long(len) {
+                return Err(error);
+            }
+
+            let alias = if plus {
+                None
+            } else {
+                resolve_alias(self.parser.aliases(), &format!("-{given}"))?
+            };
+            let c = match alias {
+                Some(Flag::Long(long)) => {
+                    // Handled as if `--long` was given here, with any `=` value.
+                    let value = chars.as_str().strip_prefix(separator);
+                    self.long(&format!("--{long}"), "--", long, value)?;
+                    if value.is_some() {
+                        break;
+                    }
+                    continue;
+                }
+                Some(Flag::Short(short)) => short,
+                None => given,
+            };
+
+            if self.settings.auto_help && !plus {
+                match c {
+                    'h' => return Err(show_help(&self.parser)),
+                    'V' => return Err(show_version(&self.parser)),
+                    _ => {}
+                }
+            }
+
+            let long = self
+                .parser
+                .short_to_long()
+                .iter()
+                .find(|(short, _)| *short == c && !plus)
+                .map(|&(_, long)| long);
+            if let Some(long) = long {
+                self.given.longs.insert(long.into());
+            }
+
+            if !plus {
+                let deprecated = self.parser.deprecated().iter().find(|(old, _)| {
+                    let mut old = old.chars();
+                    old.next() == Some('-') && old.next() == Some(given) && old.next().is_none()
+                });
+                if let Some(&(old, new)) = deprecated {
+                    self.parser.warn(deprecation(old, new));
+                }
+            }
+
+            let first = plus || self.seen_shorts.insert(c);
+            let multi = self.multi_index(&format!("-{c}")).filter(|_| !plus);
+            let handle =
+                multi.is_some() || self.check_duplicate(c.encode_utf8(&mut [0; 4]), first)?;
+            let allow_dash = long.is_some_and(|long| self.parser.value_flags().contains(&long));
+            if !handle {
+                trace!(name = %format_args!("-{c}"), "duplicate");
+                let rest = chars.as_str();
+                if rest.starts_with(separator) {
+                    break;
+                }
+                if self.takes_value(|flag| {
+                    flag.short == Some(c) || long.is_some() && flag.long == long
+                }) {
+                    // The rest of the cluster, or else the following argument, is its value.
+                    if rest.is_empty() {
+                        self.skip_value(allow_dash);
+                    }
+                    break;
+                }
+                continue;
+            }
+            let parser = &mut self.parser;
+
+            let target = match (multi, long) {
+                (Some(i), _) => Target::Values(&mut self.given.multi[i].1),
+                (None, Some(long)) => Target::Long(long),
+                (None, None) => Target::Short,
+            };
+            let of = ValueOf::Short {
+                prefix: if plus { '+' } else { '-' },
+                short: c,
+            };
+            // The rest of the cluster after `c`.
+            let rest = chars.as_str();
+            match rest.chars().next() {
+                Some(p) => {
+                    if p == separator {
+                        // Only the separator straight after `c` separates; any later one is
+                        // part of the value.
+                        let value = &rest[separator.len_utf8()..];
+                        let mut next = Attached::new(value, of);
+                        let result = short_or_plus(parser, plus, c, target, true, &mut next);
+                        if result.is_err() && next.taken {
+                            self.value_len = Some(value.len());
+                        }
+                        result?;
+
+                        if !next.taken {
+                            return Err(self.unexpected_value(c.into(), value));
+                        }
+
+                        break;
+                    } else {
+                        // A value taken mid-cluster is the rest of the cluster, as in `-ofile`.
+                        let mut next = Attached::cluster(rest, of);
+                        let result = short_or_plus(parser, plus, c, target, false, &mut next);
+                        if result.is_err() && next.taken {
+                            self.value_len = Some(rest.len());
+                        }
+                        result?;
+
+                        if next.taken {
+                            break;
+                        }
+                    }
+                }
+                None => {
+                    let settings = self.settings;
+                    let mut next = Following {
+                        args: &mut self.args,
+                        of,
+                        is_value: &|arg| settings.is_value(arg),
+                        taken_count: &self.taken_count,
+                        options_ended: self.options_ended,
+                        allow_dash,
+                    };
+                    short_or_plus(parser, plus, c, target, true, &mut next)?;
+                }
+            }
+
+            // `c` took no value, so fold a run of it like the `vv` of `-vvv` into one call,
+            // leaving out a final repeat that has an `=` value.
+            let mut count = 0;
+            while let Some(after) = chars.as_str().strip_prefix(given).filter(|_| !plus) {
+                if after.starts_with(separator) {
+                    break;
+                }
+                chars.next();
+                count += 1;
+                len += 1;
+                if let Some(error) = too_long(len) {
+                    return Err(error);
+                }
+            }
+            if count > 0 && self.check_duplicate(c.encode_utf8(&mut [0; 4]), false)? {
+                let is_last = chars.as_str().is_empty();
+                let result = match long {
+                    Some(long) => {
+                        let mut next = NoValue(ParseError::ValueMidCluster {
+                            flag: format!("-{c}"),
+                        });
+                        (0..count).try_for_each(|_| self.parser.long(long, &mut next))
+                    }
+                    None => self.parser.short_repeated(c, count, is_last),
+                };
+                match result {
+                    Err(ParseError::Unhandled) => {
+                        for _ in 0..count {
+                            self.parser.unknown_short(c)?;
+                        }
+                    }
+                    result => result?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle a positional argument. Returns whether it starts a subcommand.
+    /// A `literal` argument, from after `--` or escaped, is never a subcommand or `key=value`.
+    fn positional(&mut self, arg: &OsStr, literal: bool) -> Result<bool, ParseError> {
+        if let Some(deferred) = &mut self.deferred {
+            deferred.args.push(lossy(arg));
+            return Ok(false);
+        }
+
+        let first = self.positional_count == 0;
+        self.positional_count += 1;
+
+        if first && !literal {
+            let command = expand_subcommand(self.parser.subcommands(), &arg.to_string_lossy())?;
+            if command.is_some() {
+                self.subcommand = command;
+                return Ok(true);
+            }
+        }
+
+        if self.settings.positional_keyvals && !literal {
+            let arg = arg.to_string_lossy();
+            // A leading `=` has no key, so `=x` stays a plain positional.
+            if let Some((key, value)) = arg.split_once('=').filter(|(key, _)| !key.is_empty()) {
+                self.parser.key_value(key, value)?;
+                return Ok(false);
+            }
+        }
+
+        let settings = self.settings;
+        let mut next = Following {
+            args: &mut self.args,
+            of: ValueOf::Positional,
+            is_value: &|arg| settings.is_value(arg),
+            taken_count: &self.taken_count,
+            options_ended: self.options_ended,
+            allow_dash: false,
+        };
+        trace!(arg = %arg.to_string_lossy(), "positional");
+        let result = self.parser.argument_os(arg, &mut next);
+        let known = self.parser.subcommands();
+        match result {
+            // Only plain arguments count; one that starts a subcommand does not.
+            Ok(false) if settings.max_positionals == Some(self.given.arguments) => {
+                return Err(ParseError::TooManyArguments {
+                    max: self.given.arguments,
+                });
+            }
+            Ok(false) => self.given.arguments += 1,
+            Ok(true) => {}
+            Err(ParseError::Unhandled)
+                if first && !literal && !self.partial && !known.is_empty() =>
+            {
+                let command = arg.to_string_lossy();
+                return Err(ParseError::UnknownSubcommand {
+                    suggestion: suggest_subcommand(known, &command),
+                    command: command.into_owned(),
+                });
+            }
+            Err(_) => {}
+        }
+        result
+    }
+}
+
+/// How deeply response files may include other response files.
+const MAX_RESPONSE_FILE_DEPTH: usize = 16;
+
+/// Expand any `@path` response files in `args` into `expanded`.
+/// Returns whether a standalone `--` was seen, after which nothing is expanded.
+fn expand_response_files<'a>(
+    mut args: impl Iterator<Item = Cow<'a, OsStr>>,
+    depth: usize,
+    expanded: &mut Vec<Cow<'a, OsStr>>,
+) -> Result<bool, ParseError> {
+    while let Some(arg) = args.next() {
+        if *arg == *"--" {
+            expanded.push(arg);
+            expanded.extend(args);
+            return Ok(true);
+        }
+
+        let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix('@')) else {
+            expanded.push(arg);
+            continue;
+        };
+
+        if path.starts_with('@') {
+            // `@@arg` escapes a literal `@arg`.
+            expanded.push(Cow::Owned(path.into()));
+            continue;
+        }
+
+        if depth == MAX_RESPONSE_FILE_DEPTH {
+            return Err(ParseError::ResponseFile {
+                path: path.into(),
+                reason: "response files are nested too deeply".into(),
+            });
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|e| ParseError::ResponseFile {
+            path: path.into(),
+            reason: e.to_string().into(),
+        })?;
+        let file_args = contents
+            .split_whitespace()
+            .map(OsString::from)
+            .map(Cow::Owned);
+        if expand_response_files(file_args, depth + 1, expanded)? {
+            expanded.extend(args);
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// The subcommand in `known` fewest edits away from `arg`, if it is at most two away.
+fn suggest_subcommand(known: &[&'static str], arg: &str) -> Option<&'static str> {
+    known
+        .iter()
+        .map(|&command| (edit_distance(command, arg), command))
+        .filter(|&(distance, _)| distance <= 2)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, command)| command)
+}
+
+/// The number of characters to insert, remove or replace to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let replaced = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = replaced.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Expand `long` to the unique flag in `known` it is a prefix of.
+/// Unknown flags are returned unchanged so the parser can report them.
+fn expand_long<'a>(known: &[&'static str], long: &'a str) -> Result<&'a str, ParseError> {
+    if known.contains(&long) {
+        return Ok(long);
+    }
+
+    let candidates: Vec<_> = known
+        .iter()
+        .copied()
+        .filter(|k| k.starts_with(long))
+        .collect();
+    match candidates[..] {
+        [] => Ok(long),
+        [only] => Ok(only),
+        _ => Err(ParseError::AmbiguousFlag {
+            flag: long.into(),
+            candidates,
+        }),
+    }
+}
+
+/// Find the listed subcommand that `arg` names, in full or by a unique prefix.
+fn expand_subcommand(
+    known: &[&'static str],
+    arg: &str,
+) -> Result<Option<&'static str>, ParseError> {
+    if let Some(command) = known.iter().find(|command| **command == arg) {
+        return Ok(Some(command));
+    }
+    if arg.is_empty() {
+        return Ok(None);
+    }
+
+    let candidates: Vec<_> = known
+        .iter()
+        .copied()
+        .filter(|command| command.starts_with(arg))
+        .collect();
+    match candidates[..] {
+        [] => Ok(None),
+        [only] => Ok(Some(only)),
+        _ => Err(ParseError::AmbiguousSubcommand {
+            command: arg.into(),
+            candidates,
+        }),
+    }
+}
+
+/// A flag named in [`ArgumentParser::aliases`].
+enum Flag {
+    Long(&'static str),
+    Short(char),
+}
+
+/// Follow `aliases` from `flag`, spelled like `--name` or `-c`, to the flag it stands for.
+/// Returns `None` if `flag` is not an alias, or its target is not spelled as a flag.
+fn resolve_alias(
+    aliases: &[(&'static str, &'static str)],
+    flag: &str,
+) -> Result<Option<Flag>, ParseError> {
+    let mut target: Option<&str> = None;
+    let mut current = flag;
+    // Every step but the last must use a different alias, or there is a cycle.
+    for _ in 0..=aliases.len() {
+        let Some(&(_, next)) = aliases.iter().find(|(alias, _)| *alias == current) else {
+            let Some(target) = target else {
+                return Ok(None);
+            };
+            if let Some(long) = target.strip_prefix("--") {
+                return Ok(Some(Flag::Long(long)));
+            }
+            let mut short = target.strip_prefix('-').unwrap_or_default().chars();
+            return Ok(short
+                .next()
+                .filter(|_| short.next().is_none())
+                .map(Flag::Short));
+        };
+        target = Some(next);
+        current = next;
+    }
+    Err(ParseError::AliasCycle { flag: flag.into() })
+}
+
+/// Where a short flag goes.
+enum Target<'a> {
+    Short,
+    /// [`ArgumentParser::long`], from [`ArgumentParser::short_to_long`].
+    Long(&'static str),
+    /// The values collected for [`ArgumentParser::multi`].
+    Values(&'a mut Vec<String>),
+}
+
+/// Dispatch the short flag `c` to `target`, or to [`ArgumentParser::plus`] for a `+` flag.
+fn short_or_plus<P: ArgumentParser>(
+    parser: &mut P,
+    plus: bool,
+    c: char,
+    target: Target,
+    is_last: bool,
+    next: &mut impl Lookahead,
+) -> Result<(), ParseError> {
+    if plus {
+        validated(parser, &format!("+{c}"), next, |parser, next| {
+            parser.plus(c, is_last, next)
+        })
+    } else {
+        let flag = match target {
+            Target::Long(long) => format!("--{long}"),
+            _ => format!("-{c}"),
+        };
+        let result = validated(parser, &flag, next, |parser, next| match target {
+            Target::Short => parser.short(c, is_last, next),
+            Target::Long(long) => parser.long(long, next),
+            Target::Values(values) => {
+                values.push(next.take()?);
+                Ok(())
+            }
+        });
+        match result {
+            Err(ParseError::Unhandled) => parser.unknown_short(c),
+            result => result,
+        }
+    }
+}
+
+/// Dispatch the long flag `long`, or collect its value into `values` for
+/// [`ArgumentParser::multi`].
+fn long_or_values<P: ArgumentParser>(
+    parser: &mut P,
+    long: &str,
+    values: Option<&mut Vec<String>>,
+    next: &mut impl Lookahead,
+) -> Result<(), ParseError> {
+    validated(
+        parser,
+        &format!("--{long}"),
+        next,
+        |parser, next| match values {
+            Some(values) => {
+                values.push(next.take()?);
+                Ok(())
+            }
+            None => parser.long(long, next),
+        },
+    )
+}
+
+/// Run `handle` for `flag`, checking each value it takes with
+/// [`ArgumentParser::validate_value`] before it gets it.
+///
+/// The handler holds the parser mutably, so the values it could take are checked before it
+/// runs and each verdict is applied as its value is taken. Values past those are checked
+/// once it returns.
+fn validated<P: ArgumentParser>(
+    parser: &mut P,
+    flag: &str,
+    next: &mut impl Lookahead,
+    handle: impl FnOnce(&mut P, &mut dyn Next) -> Result<(), ParseError>,
+) -> Result<(), ParseError> {
+    trace!(name = flag, "flag");
+    let mut verdicts = Verdicts::default();
+    next.ahead(&mut |value| {
+        if let Err(reason) = parser.validate_value(flag, value) {
+            verdicts.rejected.push((verdicts.ahead, reason));
+        }
+        verdicts.ahead += 1;
+    });
+    let mut checked = Checked {
+        next,
+        verdicts,
+        error: None,
+    };
+    let result = handle(parser, &mut checked);
+    let Checked {
+        verdicts, error, ..
+    } = checked;
+    if let Some(e) = error {
+        return Err(e);
+    }
+    result?;
+
+    for value in verdicts.unchecked {
+        if let Err(reason) = parser.validate_value(flag, &value) {
+            return Err(ParseError::InvalidValue {
+                flag: next.flag(),
+                value,
+                reason,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn deprecation(old: &str, new: &str) -> Cow<'static, str> {
+    format!("'{old}' is deprecated, use '{new}'").into()
+}
+
+fn show_help<P: ArgumentParser>(parser: &P) -> ParseError {
+    println!("{}", parser.help_styled(use_color()));
+    ParseError::Help
+}
+
+fn show_version<P: ArgumentParser>(parser: &P) -> ParseError {
+    println!("{}", parser.version());
+    ParseError::Version
+}
+
+fn empty_args<'a>() -> Args<'a> {
+    Args::new(std::iter::empty())
+}
+
+pub(crate) fn lossy<'a>(s: impl Into<Cow<'a, OsStr>>) -> String {
+    match s.into() {
+        Cow::Borrowed(s) => s.to_string_lossy().into_owned(),
+        Cow::Owned(s) => s
+            .into_string()
+            .unwrap_or_else(|s| s.to_string_lossy().into_owned()),
+    }
+}
+
+/// Whether `s` is a number such as `-5`, `-5.0` or `-5e3`, with an optional sign.
+fn is_number(s: &str) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (s, None),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let mantissa_ok = !(int.is_empty() && frac.is_empty()) && all_digits(int) && all_digits(frac);
+    let exponent_ok = exponent.is_none_or(|e| {
+        let e = e.strip_prefix(['-', '+']).unwrap_or(e);
+        !e.is_empty() && all_digits(e)
+    });
+
+    mantissa_ok && exponent_ok
+}
@@ -0,0 +1,354 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+
+/// The reason parsing stopped without producing a parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The help message was requested and printed; the program should exit successfully.
+    Help,
+    /// The version was requested and printed; the program should exit successfully.
+    Version,
+    /// No arguments were given at all, and
+    /// [`allow_no_args`](crate::ArgumentParser::allow_no_args) is off.
+    NoArguments,
+    /// A flag was given a value with `=` but did not take it.
+    /// `flag` is the long flag name or short flag character, without its prefix.
+    UnexpectedValue { flag: String, value: String },
+    /// A flag was given more than once, and
+    /// [`on_duplicate`](crate::ArgumentParser::on_duplicate) does not allow it.
+    DuplicateFlag { flag: String },
+    /// A flag wanted a value but no arguments were left.
+    /// `flag` is spelled as given, like `--output` or `-o`.
+    MissingValue { flag: String },
+    /// A flag listed in [`required`](crate::ArgumentParser::required) was not given.
+    /// `flag` is spelled with its `--`.
+    MissingFlag { flag: String },
+    /// Two flags from the same group in
+    /// [`exclusive_groups`](crate::ArgumentParser::exclusive_groups) were given, spelled
+    /// with their `--` in the order of the group.
+    ExclusiveFlags { first: String, second: String },
+    /// A flag wanted more values than were left before the next flag or the end.
+    /// `flag` is not known for positional arguments.
+    TooFewValues {
+        flag: Option<String>,
+        expected: usize,
+        got: usize,
+    },
+    /// A value could not be parsed by [`parse_value`](crate::parse_value).
+    /// `flag` is spelled as given, and not known for positional arguments.
+    InvalidValue {
+        flag: Option<String>,
+        value: String,
+        reason: Cow<'static, str>,
+    },
+    /// A flag wanted a value but the next argument was a flag.
+    ExpectedValue { got: String },
+    /// A flag had an empty name, as in `--=value` or `-=value`.
+    EmptyFlag { arg: String },
+    /// A long flag was given its value in the next argument, and
+    /// [`long_value_mode`](crate::ArgumentParser::long_value_mode) is
+    /// [`RequireEquals`](crate::LongValueMode::RequireEquals). `flag` is spelled as given.
+    AttachedValueRequired { flag: String, separator: char },
+    /// A long flag was given an attached value, and
+    /// [`long_value_mode`](crate::ArgumentParser::long_value_mode) is
+    /// [`ForbidEquals`](crate::LongValueMode::ForbidEquals). `flag` is spelled as given.
+    AttachedValueForbidden { flag: String, separator: char },
+    /// A long flag's value started with another separator, as in `--flag==value`, and
+    /// [`strict_equals`](crate::ArgumentParser::strict_equals) is on.
+    /// `flag` is spelled as given.
+    DoubledSeparator { flag: String, separator: char },
+    /// A short flag tried to take a value where it has none, as each repeat in `-aa` but
+    /// the last does. A flag that does have the rest of its cluster after it can take that,
+    /// as in `-ofile`.
+    ValueMidCluster { flag: String },
+    /// A single short flag cluster held more than
+    /// [`max_short_chain`](crate::ArgumentParser::max_short_chain) flags.
+    ShortChainTooLong { max: usize },
+    /// An abbreviated long flag matched more than one known flag.
+    AmbiguousFlag {
+        flag: String,
+        candidates: Vec<&'static str>,
+    },
+    /// Following [`aliases`](crate::ArgumentParser::aliases) from this flag went round in a
+    /// cycle.
+    AliasCycle { flag: String },
+    /// An abbreviated subcommand matched more than one of
+    /// [`subcommands`](crate::ArgumentParser::subcommands).
+    AmbiguousSubcommand {
+        command: String,
+        candidates: Vec<&'static str>,
+    },
+    /// The first positional argument was none of
+    /// [`subcommands`](crate::ArgumentParser::subcommands), and
+    /// [`argument`](crate::ArgumentParser::argument) declined it. `suggestion` is the closest
+    /// subcommand, if any is close enough to be a likely typo.
+    UnknownSubcommand {
+        command: String,
+        suggestion: Option<&'static str>,
+    },
+    /// A subcommand was given no arguments, and
+    /// [`subcommand_requires_args`](crate::ArgumentParser::subcommand_requires_args) is on
+    /// for it.
+    EmptySubcommand { command: String },
+    /// A response file could not be expanded.
+    ResponseFile {
+        path: String,
+        reason: Cow<'static, str>,
+    },
+    /// A [`config_file`](crate::ArgumentParser::config_file) could not be read, or had an
+    /// error on the given (1-based) line.
+    ConfigFile {
+        path: String,
+        line: Option<usize>,
+        reason: Cow<'static, str>,
+    },
+    /// The flag in a [`config_file`](crate::ArgumentParser::config_file) entry on the given
+    /// (1-based) line failed with `error`, as it would have on the command line.
+    ConfigEntry {
+        path: String,
+        line: usize,
+        error: Box<ParseError>,
+    },
+    /// Arguments could not be read for
+    /// [`stdin_args_flag`](crate::ArgumentParser::stdin_args_flag).
+    StdinArgs { reason: Cow<'static, str> },
+    /// A command line given to [`parse_str`](crate::parse_str) ended inside this quote.
+    UnterminatedQuote(char),
+    /// A command line given to [`parse_str`](crate::parse_str) ended with a backslash.
+    TrailingEscape,
+    /// More positional arguments were given than
+    /// [`max_positionals`](crate::ArgumentParser::max_positionals) allows.
+    TooManyArguments { max: usize },
+    /// Fewer positional arguments were given than
+    /// [`min_positionals`](crate::ArgumentParser::min_positionals) requires.
+    TooFewArguments { min: usize, got: usize },
+    /// A positional argument wanted the next argument but none were left.
+    MissingArgument,
+    /// A handler declined an argument. [`parse_partial`](crate::parse_partial) stops here
+    /// instead of failing.
+    Unhandled,
+    /// An error caused by the argument at the given (1-based) index.
+    /// See [`render_diagnostic`](crate::render_diagnostic) for pointing it out.
+    At {
+        index: usize,
+        /// The byte range within the argument of the value the error is about, for a value
+        /// attached to its flag like the `val` of `--flag=val`.
+        value: Option<Range<usize>>,
+        error: Box<ParseError>,
+    },
+    /// An error returned by the parser itself.
+    UserError(Cow<'static, str>),
+    /// The messages of every error found, when
+    /// [`collect_errors`](crate::ArgumentParser::collect_errors) is on and there was more
+    /// than one.
+    Multiple(Vec<Cow<'static, str>>),
+}
+
+impl ParseError {
+    /// Whether this is a requested early exit rather than a failure.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Help | Self::Version)
+    }
+
+    /// Whether parsing can carry on after this error for
+    /// [`collect_errors`](crate::ArgumentParser::collect_errors). Missing values cannot, as
+    /// the following arguments would be misread.
+    pub(crate) fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            Self::Help
+                | Self::Version
+                | Self::MissingValue { .. }
+                | Self::TooFewValues { .. }
+                | Self::ExpectedValue { .. }
+                | Self::MissingArgument
+        )
+    }
+
+    /// Attach the index of the argument that caused this error, and the range of its
+    /// attached value if the error is about that.
+    pub(crate) fn at(self, index: usize, value: Option<Range<usize>>) -> Self {
+        match self {
+            Self::Help | Self::Version | Self::At { .. } => self,
+            error => Self::At {
+                index,
+                value,
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Help => f.write_str("Help was requested"),
+            Self::Version => f.write_str("Version was requested"),
+            Self::NoArguments => f.write_str("No arguments given"),
+            Self::UnexpectedValue { flag, value } => {
+                write!(
+                    f,
+                    "Flag '{flag}' was given argument '{value}' without using it"
+                )
+            }
+            Self::MissingValue { flag } => {
+                write!(f, "Flag '{flag}' expected a value but none was given")
+            }
+            Self::MissingFlag { flag } => write!(f, "Missing required flag '{flag}'"),
+            Self::ExclusiveFlags { first, second } => {
+                write!(f, "Flags '{first}' and '{second}' are mutually exclusive")
+            }
+            Self::TooFewValues {
+                flag: Some(flag),
+                expected,
+                got,
+            } => write!(f, "Flag '{flag}' expected {expected} values but got {got}"),
+            Self::TooFewValues {
+                flag: None,
+                expected,
+                got,
+            } => write!(f, "Expected {expected} values but got {got}"),
+            Self::InvalidValue {
+                flag: Some(flag),
+                value,
+                reason,
+            } => write!(f, "Invalid value '{value}' for '{flag}': {reason}"),
+            Self::InvalidValue {
+                flag: None,
+                value,
+                reason,
+            } => write!(f, "Invalid value '{value}': {reason}"),
+            Self::ExpectedValue { got } => write!(f, "Expected value, got flag {got}"),
+            Self::EmptyFlag { arg } => write!(f, "Empty flag name in '{arg}'"),
+            Self::AttachedValueRequired { flag, separator } => write!(
+                f,
+                "Flag '{flag}' needs its value attached, as in '{flag}{separator}value'"
+            ),
+            Self::AttachedValueForbidden { flag, separator } => write!(
+                f,
+                "Flag '{flag}' does not allow '{separator}', give its value as the next argument"
+            ),
+            Self::DoubledSeparator { flag, separator } => {
+                write!(f, "Unexpected '{separator}' after flag '{flag}'")
+            }
+            Self::DuplicateFlag { flag } => write!(f, "Flag '{flag}' was given more than once"),
+            Self::ValueMidCluster { flag } => write!(
+                f,
+                "Flag '{flag}' requires a value and cannot be used mid-cluster"
+            ),
+            Self::ShortChainTooLong { max } => {
+                write!(
+                    f,
+                    "Too many flags in one short flag cluster (at most {max})"
+                )
+            }
+            Self::AmbiguousFlag { flag, candidates } => {
+                write!(f, "Flag '{flag}' is ambiguous, could be any of: ")?;
+                f.write_str(&candidates.join(", "))
+            }
+            Self::AliasCycle { flag } => write!(f, "Alias '{flag}' is part of a cycle"),
+            Self::AmbiguousSubcommand {
+                command,
+                candidates,
+            } => {
+                write!(f, "Subcommand '{command}' is ambiguous, could be any of: ")?;
+                f.write_str(&candidates.join(", "))
+            }
+            Self::UnknownSubcommand {
+                command,
+                suggestion,
+            } => {
+                write!(f, "Unknown subcommand '{command}'")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, "; did you mean '{suggestion}'?"),
+                    None => Ok(()),
+                }
+            }
+            Self::EmptySubcommand { command } => {
+                write!(f, "Subcommand '{command}' needs arguments")
+            }
+            Self::ResponseFile { path, reason } => {
+                write!(f, "Could not read response file '{path}': {reason}")
+            }
+            Self::ConfigFile {
+                path,
+                line: Some(line),
+                reason,
+            } => write!(f, "Error in config file '{path}' on line {line}: {reason}"),
+            Self::ConfigFile { path, reason, .. } => {
+                write!(f, "Could not read config file '{path}': {reason}")
+            }
+            Self::ConfigEntry { path, line, error } => {
+                write!(f, "Error in config file '{path}' on line {line}: {error}")
+            }
+            Self::StdinArgs { reason } => {
+                write!(f, "Could not read arguments from standard input: {reason}")
+            }
+            Self::UnterminatedQuote(quote) => write!(f, "Unterminated {quote} quote"),
+            Self::TrailingEscape => f.write_str("Trailing backslash with nothing to escape"),
+            Self::TooManyArguments { max } => {
+                write!(f, "Too many arguments (expected at most {max})")
+            }
+            Self::TooFewArguments { min, got } => {
+                write!(f, "Too few arguments (expected at least {min}, got {got})")
+            }
+            Self::MissingArgument => f.write_str("No argument next"),
+            Self::Unhandled => f.write_str("Unrecognized argument"),
+            Self::At { index, error, .. } => write!(f, "at argument {index}: {error}"),
+            Self::UserError(message) => f.write_str(message),
+            Self::Multiple(messages) => f.write_str(&messages.join("\n")),
+        }
+    }
+}
+
+/// The message for a value that is not one of those `expected`, worded like
+/// [`ParseError::InvalidValue`]. Use this and [`unknown_flag`] for the errors handlers
+/// return themselves, to keep their wording the same as the crate's.
+///
+/// ```
+/// # use krh_args_parser::{invalid_value, unknown_flag, ParseError};
+/// assert_eq!(
+///     invalid_value("--color", "red", "auto, always or never"),
+///     "Invalid value 'red' for '--color': expected auto, always or never"
+/// );
+/// assert_eq!(unknown_flag("-x"), "Unknown flag '-x'");
+///
+/// let error: ParseError = unknown_flag("--foo").into();
+/// assert_eq!(error.to_string(), "Unknown flag '--foo'");
+/// ```
+pub fn invalid_value(flag: &str, got: &str, expected: &str) -> Cow<'static, str> {
+    let error = ParseError::InvalidValue {
+        flag: Some(flag.into()),
+        value: got.into(),
+        reason: format!("expected {expected}").into(),
+    };
+    error.to_string().into()
+}
+
+/// The message for a flag the parser does not know, spelled as given like `--foo` or `-x`.
+pub fn unknown_flag(flag: &str) -> Cow<'static, str> {
+    format!("Unknown flag '{flag}'").into()
+}
+
+// `At` already displays the error it wraps, so it has no `source`, which would print it twice
+// in error reports that walk the chain.
+impl std::error::Error for ParseError {}
+
+impl From<Cow<'static, str>> for ParseError {
+    fn from(message: Cow<'static, str>) -> Self {
+        Self::UserError(message)
+    }
+}
+
+impl From<&'static str> for ParseError {
+    fn from(message: &'static str) -> Self {
+        Self::UserError(message.into())
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        Self::UserError(message.into())
+    }
+}
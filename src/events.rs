@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+
+use crate::token::{classify, Token};
+use crate::ParseError;
+
+/// A single piece of a command line, as yielded by [`events`](crate::events).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgEvent {
+    /// A long flag `--name`, with the value from `--name=value` if given.
+    Long { name: String, value: Option<String> },
+    /// A short flag, with the value from `-c=value` if given.
+    /// A cluster like `-abc` yields one event per flag.
+    Short { c: char, value: Option<String> },
+    /// A positional argument, including everything after [`Separator`](Self::Separator).
+    Positional(String),
+    /// A standalone `--`, after which every argument is positional.
+    Separator,
+}
+
+/// The iterator returned by [`events`](crate::events).
+pub(crate) struct Events<I> {
+    args: I,
+    /// Events left over from a short flag cluster.
+    pending: VecDeque<ArgEvent>,
+    options_ended: bool,
+}
+
+impl<I> Events<I> {
+    pub(crate) fn new(args: I) -> Self {
+        Self {
+            args,
+            pending: VecDeque::new(),
+            options_ended: false,
+        }
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for Events<I> {
+    type Item = Result<ArgEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.pending.pop_front() {
+            return Some(Ok(event));
+        }
+
+        let arg = self.args.next()?;
+        if self.options_ended {
+            return Some(Ok(ArgEvent::Positional(arg)));
+        }
+        match classify(&arg) {
+            Token::Separator => {
+                self.options_ended = true;
+                Some(Ok(ArgEvent::Separator))
+            }
+            Token::Long { name: "", .. } => Some(Err(ParseError::EmptyFlag { arg })),
+            Token::Long { name, value } => Some(Ok(ArgEvent::Long {
+                name: name.to_owned(),
+                value: value.map(str::to_owned),
+            })),
+            Token::Short(s) if s.starts_with('=') => Some(Err(ParseError::EmptyFlag { arg })),
+            Token::Short(s) => {
+                let mut chars = s.chars();
+                while let Some(c) = chars.next() {
+                    // Only the `=` straight after a flag gives it a value.
+                    if let Some(value) = chars.as_str().strip_prefix('=') {
+                        let value = Some(value.to_owned());
+                        self.pending.push_back(ArgEvent::Short { c, value });
+                        break;
+                    }
+                    self.pending.push_back(ArgEvent::Short { c, value: None });
+                }
+                self.pending.pop_front().map(Ok)
+            }
+            Token::Positional(_) => Some(Ok(ArgEvent::Positional(arg))),
+        }
+    }
+}
@@ -1,151 +1,2051 @@
 use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::io::{IsTerminal, Read};
+use std::path::PathBuf;
+
+mod builder;
+pub mod completions;
+#[cfg(feature = "serde")]
+mod deserialize;
+mod diagnostic;
+mod driver;
+mod error;
+mod events;
+mod man;
+mod next;
+mod positionals;
+mod spec;
+mod split;
+pub mod testing;
+mod token;
+
+pub use builder::{BuiltParser, ParserBuilder};
+#[cfg(feature = "serde")]
+pub use deserialize::Deserialized;
+pub use diagnostic::render_diagnostic;
+pub use error::{invalid_value, unknown_flag, ParseError};
+pub use events::ArgEvent;
+/// Derive [`ArgumentParser`] from `#[arg(...)]` field attributes.
+#[cfg(feature = "derive")]
+pub use krh_args_parser_derive::ArgumentParser;
+pub use man::man_page;
+pub use next::{parse_value, Next, ValueSource};
+use next::{Attached, NoValue, ValueOf};
+pub use positionals::Positionals;
+pub use spec::{default_help, CommandSpec, FlagSpec};
+pub use token::{classify, token_at, Token, TokenContext, TokenKind};
+
+/// What to do when a flag is given more than once.
+/// See [`ArgumentParser::on_duplicate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Handle every occurrence, so a later value replaces an earlier one (or adds to a list).
+    LastWins,
+    /// Handle only the first occurrence. Later ones are not passed to the handler. A value
+    /// attached to one is skipped, as is the value following one that
+    /// [`describe`](ArgumentParser::describe) says takes a value.
+    FirstWins,
+    /// Fail with [`ParseError::DuplicateFlag`].
+    Error,
+}
+
+/// Which arguments are long flags. See [`ArgumentParser::long_prefix_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongPrefixMode {
+    /// Long flags start with `--`, and `-abc` is a cluster of short flags.
+    DoubleDash,
+    /// Long flags may also start with a single `-`, as in `find -name` or `java -jar`.
+    /// A flag name of more than one character, like `-name` or `-name=value`, is long;
+    /// one character, like `-v` or `-o=value`, is short. Short flags cannot be clustered or
+    /// have values attached, as in `-ofile`.
+    SingleDash,
+}
+
+/// How long flags may be given their values. See [`ArgumentParser::long_value_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongValueMode {
+    /// Values may be attached, as in `--flag=value`, or be the next argument.
+    Either,
+    /// Values must be attached. Taking one from the next argument fails with
+    /// [`ParseError::AttachedValueRequired`], which a flag with an optional value may ignore
+    /// to leave the argument as a positional.
+    RequireEquals,
+    /// Values must be the next argument. Attaching one fails with
+    /// [`ParseError::AttachedValueForbidden`].
+    ForbidEquals,
+}
 
 pub trait ArgumentParser: Default {
     /// Get the application version.
-    fn version() -> Cow<'static, str> {
-        concat!(env!("CARGO_PKG_NAME"), " v", env!("CARGO_PKG_VERSION")).into()
+    /// By default this is the package name and version, followed by any
+    /// [`version_extra`](Self::version_extra) in parentheses.
+    fn version(&self) -> Cow<'static, str> {
+        let version = concat!(env!("CARGO_PKG_NAME"), " v", env!("CARGO_PKG_VERSION"));
+        match self.version_extra() {
+            Some(extra) => format!("{version} ({extra})").into(),
+            None => version.into(),
+        }
+    }
+
+    /// Build details for the default [`version`](Self::version), like a git hash or build
+    /// date.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use krh_args_parser::ArgumentParser;
+    /// #[derive(Default)]
+    /// struct Tool;
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn version_extra(&self) -> Option<Cow<'static, str>> {
+    ///         Some("abc123".into())
+    ///     }
+    /// }
+    ///
+    /// let version = concat!("krh_args_parser v", env!("CARGO_PKG_VERSION"), " (abc123)");
+    /// assert_eq!(Tool.version(), version);
+    /// ```
+    fn version_extra(&self) -> Option<Cow<'static, str>> {
+        None
     }
 
     /// Get your help message.
-    /// It is recommended to also print your version here using [`version`].
-    fn help() -> Cow<'static, str>;
+    /// It is recommended to also print your version here using [`version`](Self::version).
+    /// [`CommandSpec::options_help`] can format the flags from [`describe`](Self::describe),
+    /// with their defaults.
+    ///
+    /// By default this is [`default_help`] for [`describe`](Self::describe).
+    fn help(&self) -> Cow<'static, str> {
+        default_help(&self.describe()).into()
+    }
 
-    /// Handle a long flag.
-    fn long(
-        &mut self,
-        long: &str,
-        next: &mut dyn FnMut() -> Result<String, Cow<'static, str>>,
-    ) -> Result<(), Cow<'static, str>>;
-    /// Handle a short flag.
-    fn short(
+    /// Get your help message, with ANSI styling like colored headers if `use_color` is set.
+    /// The automatic `--help` calls this with [`use_color`]. By default the styling is left
+    /// out, and this is [`help`](Self::help) unchanged.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use krh_args_parser::ArgumentParser;
+    /// #[derive(Default)]
+    /// struct Tool;
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn help(&self) -> Cow<'static, str> {
+    ///         "Usage: tool [OPTIONS]".into()
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Tool.help_styled(false), "Usage: tool [OPTIONS]");
+    /// ```
+    fn help_styled(&self, use_color: bool) -> Cow<'static, str> {
+        let _ = use_color;
+        self.help()
+    }
+
+    /// Whether `--help`/`-h` and `--version`/`-V` should be handled automatically.
+    /// When enabled, they print [`help`](Self::help) or [`version`](Self::version) and parsing
+    /// stops with [`ParseError::Help`] or [`ParseError::Version`].
+    fn auto_help(&self) -> bool {
+        true
+    }
+
+    /// Whether an empty command line is allowed, for tools that do something useful without
+    /// arguments. Otherwise it fails with [`ParseError::NoArguments`].
+    /// When allowed, the parser is returned after [`finish`](Self::finish).
+    fn allow_no_args(&self) -> bool {
+        false
+    }
+
+    /// Whether to carry on past errors from handlers, so every problem with the command line
+    /// is reported at once. Parsing still stops at a flag missing its value, which would
+    /// throw off the arguments after it. If more than one error is found, they are returned
+    /// together as [`ParseError::Multiple`].
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool;
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn collect_errors(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         Err(format!("Unknown flag '--{long}'").into())
+    ///     }
+    /// }
+    ///
+    /// let error = parse_slice::<Tool>(&["--foo", "--bar"]).err().unwrap();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "at argument 1: Unknown flag '--foo'\nat argument 2: Unknown flag '--bar'"
+    /// );
+    /// ```
+    fn collect_errors(&self) -> bool {
+        false
+    }
+
+    /// Called with the (1-based) index of each argument before it is handled.
+    /// Values taken through `next` count as arguments too.
+    fn argument_index(&mut self, index: usize) {
+        let _ = index;
+    }
+
+    /// Receive the program name (argv\[0\]) before any arguments are handled.
+    /// Useful for producing a correct usage line in [`help`](Self::help).
+    fn program_name(&mut self, name: &str) {
+        let _ = name;
+    }
+
+    /// Called with `true` by [`parse_dry_run`] before any arguments are handled.
+    ///
+    /// While on, handlers should validate their arguments and return the same errors as
+    /// usual, but skip side effects like file writes or network calls, so a command line can
+    /// be checked without running it. Parsing itself is unchanged.
+    fn set_dry_run(&mut self, on: bool) {
+        let _ = on;
+    }
+
+    /// Handle a long flag. By default every long flag is declined with
+    /// [`ParseError::Unhandled`], so is unknown.
+    /// Only the first `=` separates a value, so `--define=KEY=VALUE` gives the value
+    /// `KEY=VALUE`, the same as `--define KEY=VALUE`.
+    ///
+    /// A switch that never calls `next` needs no value, even at the end of the arguments.
+    /// A value given with `=` must be taken, or parsing fails with
+    /// [`ParseError::UnexpectedValue`]; a following argument that is not taken is handled
+    /// as usual, so it is a positional in `--verbose x`.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     verbose: bool,
+    ///     files: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn long(&mut self, _long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.verbose = true;
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         self.files.push(arg.into());
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["a", "--verbose"]).unwrap();
+    /// assert!(tool.verbose);
+    /// assert_eq!(tool.files, ["a"]);
+    ///
+    /// let tool = parse_slice::<Tool>(&["--verbose", "x"]).unwrap();
+    /// assert!(tool.verbose);
+    /// assert_eq!(tool.files, ["x"]);
+    ///
+    /// assert_eq!(
+    ///     parse_slice::<Tool>(&["--verbose=x"]).err().unwrap().to_string(),
+    ///     "at argument 1: Flag 'verbose' was given argument 'x' without using it"
+    /// );
+    /// ```
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        let _ = (long, next);
+        Err(ParseError::Unhandled)
+    }
+
+    /// The full set of long flags, if known.
+    /// When non-empty, an unambiguous prefix like `--verb` is expanded to the only
+    /// matching flag (e.g. `verbose`) before calling [`long`](Self::long).
+    /// An exact match always wins.
+    fn known_longs(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Long flags, without their `--`, whose value may start with `-`, as in
+    /// `--message -x`. For these, [`Next::take`] acts like [`Next::take_allow_dash`], so
+    /// the next argument is taken unless it is a `--` separator.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Commit {
+    ///     message: String,
+    ///     author: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Commit {
+    ///     fn value_flags(&self) -> &[&'static str] {
+    ///         &["message"]
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "message" => self.message = next.take()?,
+    ///             "author" => self.author = next.take()?,
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(parse_slice::<Commit>(&["--message", "-x"]).unwrap().message, "-x");
+    /// assert_eq!(
+    ///     parse_slice::<Commit>(&["--author", "-x"]).err().unwrap().to_string(),
+    ///     "at argument 1: Expected value, got flag -x"
+    /// );
+    /// ```
+    fn value_flags(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Flags that may be given many times, spelled like `-I` or `--include`, whose values
+    /// are collected instead of being passed to [`short`](Self::short) or
+    /// [`long`](Self::long). They are given to [`multi`](Self::multi) once parsing is done.
+    /// Repeats are expected, so [`on_duplicate`](Self::on_duplicate) is not asked about
+    /// them. [`aliases`](Self::aliases) can give one flag several spellings.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Cc {
+    ///     include: Vec<String>,
+    ///     verbose: bool,
+    /// }
+    ///
+    /// impl ArgumentParser for Cc {
+    ///     fn multi_value_flags(&self) -> &[&'static str] {
+    ///         &["-I"]
+    ///     }
+    ///
+    ///     fn multi(&mut self, _flag: &str, values: Vec<String>) -> Result<(), ParseError> {
+    ///         self.include = values;
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn short(&mut self, _short: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.verbose = true;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let cc = parse_slice::<Cc>(&["-I", "a", "-v", "-Ib", "-vI=c"]).unwrap();
+    /// assert_eq!(cc.include, ["a", "b", "c"]);
+    /// assert!(cc.verbose);
+    /// ```
+    fn multi_value_flags(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Receive every value given for one of [`multi_value_flags`](Self::multi_value_flags),
+    /// spelled as listed there, before [`finish`](Self::finish). Not called for flags that
+    /// were not given.
+    fn multi(&mut self, flag: &str, values: Vec<String>) -> Result<(), ParseError> {
+        let _ = (flag, values);
+        Ok(())
+    }
+
+    /// Check a value taken by a flag's handler, with the flag spelled like `--long` or `-s`
+    /// after resolving [`aliases`](Self::aliases) and abbreviations. An error fails with
+    /// [`ParseError::InvalidValue`] giving the reason.
+    ///
+    /// The values a handler could take are checked before it runs, against the parser as the
+    /// flags before it left it, so a rejected one never reaches the handler, even with
+    /// [`collect_errors`](Self::collect_errors) or [`parse_lenient`]. That includes
+    /// arguments the handler then leaves, so this should only check. [`Next::take_all`] gives
+    /// no values if any is rejected, and [`Next::rest`] ends before one. Values from
+    /// [`Next::rest`] past the next flag are checked once the handler returns.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use krh_args_parser::{parse_lenient, parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     jobs: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn validate_value(&self, flag: &str, value: &str) -> Result<(), Cow<'static, str>> {
+    ///         match flag {
+    ///             "--jobs" if !value.parse::<usize>().is_ok_and(|jobs| jobs > 0) => {
+    ///                 Err("must be a positive integer".into())
+    ///             }
+    ///             _ => Ok(()),
+    ///         }
+    ///     }
+    ///
+    ///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.jobs = next.take()?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(parse_slice::<Tool>(&["--jobs", "4"]).unwrap().jobs, "4");
+    /// assert_eq!(
+    ///     parse_slice::<Tool>(&["--jobs=0"]).err().unwrap().to_string(),
+    ///     "at argument 1: Invalid value '0' for '--jobs': must be a positive integer"
+    /// );
+    ///
+    /// let args = ["--jobs=2", "--jobs", "0"].map(String::from);
+    /// let (tool, result) = parse_lenient::<Tool>(args.into_iter());
+    /// assert_eq!(tool.jobs, "2");
+    /// assert!(result.is_err());
+    /// ```
+    fn validate_value(&self, flag: &str, value: &str) -> Result<(), Cow<'static, str>> {
+        let _ = (flag, value);
+        Ok(())
+    }
+
+    /// What to do when a long or short flag is given again, with its name as passed to
+    /// [`long`](Self::long) or [`short`](Self::short). Long and short spellings are tracked
+    /// separately, and a run like `-vv` counts as a duplicate. Every occurrence is handled
+    /// by default.
+    ///
+    /// For [`DuplicatePolicy::FirstWins`], later occurrences are skipped without calling the
+    /// handler. A value attached to one is skipped with it, and so is the following argument
+    /// if [`describe`](Self::describe) lists the flag as taking a value.
+    ///
+    /// A long flag is the same flag however its value is given, and after expanding
+    /// abbreviations and [`aliases`](Self::aliases):
+    ///
+    /// ```
+    /// # use krh_args_parser::{
+    /// #     parse_slice, ArgumentParser, CommandSpec, DuplicatePolicy, FlagSpec, Next, ParseError,
+    /// # };
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     first_wins: bool,
+    ///     opt: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn on_duplicate(&self, _flag: &str) -> DuplicatePolicy {
+    ///         match self.first_wins {
+    ///             true => DuplicatePolicy::FirstWins,
+    ///             false => DuplicatePolicy::Error,
+    ///         }
+    ///     }
+    ///
+    ///     fn known_longs(&self) -> &[&'static str] {
+    ///         &["option"]
+    ///     }
+    ///
+    ///     fn describe(&self) -> CommandSpec {
+    ///         CommandSpec::new("tool").flag(FlagSpec::long("option").takes_value())
+    ///     }
+    ///
+    ///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.opt = next.take()?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// for (args, index) in [
+    ///     (["--option", "a", "--option=b"], 3),
+    ///     (["--option=a", "--opt", "b"], 2),
+    /// ] {
+    ///     assert_eq!(
+    ///         parse_slice::<Tool>(&args).err().unwrap().to_string(),
+    ///         format!("at argument {index}: Flag 'option' was given more than once")
+    ///     );
+    ///
+    ///     let tool = Tool { first_wins: true, ..Tool::default() };
+    ///     let args = args.map(String::from).into_iter();
+    ///     assert_eq!(krh_args_parser::parse_with(tool, args).unwrap().opt, "a");
+    /// }
+    /// ```
+    fn on_duplicate(&self, flag: &str) -> DuplicatePolicy {
+        let _ = flag;
+        DuplicatePolicy::LastWins
+    }
+
+    /// Deprecated flags and their replacements, like `("--old", "--new")` or `("-o", "-n")`.
+    /// A deprecated flag still works, but first [`warn`](Self::warn)s with a message like
+    /// `'--old' is deprecated, use '--new'`.
+    fn deprecated(&self) -> &[(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Other names for flags, as `(alias, flag)` pairs like `("--debug", "--verbose")` or
+    /// `("-?", "--help")`. Handlers only ever see the flag an alias stands for, which may
+    /// itself be an alias. A short alias of a long flag takes its value from `=` or the
+    /// next argument.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     flags: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn aliases(&self) -> &[(&'static str, &'static str)] {
+    ///         &[("--debug", "--verbose"), ("--trace", "--debug"), ("-d", "--debug"), ("-Q", "-q")]
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.flags.push(format!("--{long}"));
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn short(&mut self, short: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.flags.push(format!("-{short}"));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["--debug", "--trace", "-dQ"]).unwrap();
+    /// assert_eq!(tool.flags, ["--verbose", "--verbose", "--verbose", "-q"]);
+    /// ```
+    fn aliases(&self) -> &[(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Short flags handled by [`long`](Self::long) as the given long flag, so each option
+    /// is only implemented once. Unlike an [alias](Self::aliases), the short flag keeps its
+    /// cluster semantics: `-ofile` and `-vo file` give `o`'s long flag its value the same
+    /// way [`short`](Self::short) would get it. Short flags without a long flag here still
+    /// go to `short`.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     verbose: usize,
+    ///     output: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn short_to_long(&self) -> &[(char, &'static str)] {
+    ///         &[('v', "verbose"), ('o', "output")]
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "verbose" => self.verbose += 1,
+    ///             "output" => self.output = next.take()?,
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["-v", "--verbose", "-vvofile"]).unwrap();
+    /// assert_eq!((tool.verbose, tool.output.as_str()), (4, "file"));
+    /// assert_eq!(parse_slice::<Tool>(&["-o", "out"]).unwrap().output, "out");
+    /// assert_eq!(
+    ///     parse_slice::<Tool>(&["-ox"]).unwrap().output,
+    ///     "x"
+    /// );
+    /// ```
+    fn short_to_long(&self) -> &[(char, &'static str)] {
+        &[]
+    }
+
+    /// Report a problem that does not stop parsing, such as a
+    /// [`deprecated`](Self::deprecated) flag. Prints to stderr by default.
+    fn warn(&mut self, message: Cow<'static, str>) {
+        eprintln!("warning: {message}");
+    }
+
+    /// Handle a long flag that [`long`](Self::long) declined with [`ParseError::Unhandled`].
+    /// `flag` is the whole argument as given, like `--name=value`, so a wrapper can collect
+    /// it to pass on. A value in the following argument is handled as a positional.
+    /// By default the flag stays unhandled, which is an error unless using [`parse_partial`].
+    fn unknown_long(&mut self, flag: &str) -> Result<(), ParseError> {
+        let _ = flag;
+        Err(ParseError::Unhandled)
+    }
+
+    /// Whether an argument `@path` should be replaced by the whitespace-separated arguments
+    /// in the file at `path`. Response files may be nested, and `@@arg` passes `@arg` through
+    /// unchanged. Arguments after a standalone `--` are never expanded.
+    fn allow_response_files(&self) -> bool {
+        false
+    }
+
+    /// Whether `--no-<flag>` should be handled by [`long_negated`](Self::long_negated).
+    /// Off by default so flags that really start with `no-` are unaffected.
+    ///
+    /// Only the leading `no-` is removed, so names with dashes of their own work as usual:
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     given: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn allow_negation(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         let value = next.take().unwrap_or_else(|_| "true".into());
+    ///         self.given.push(format!("{long}={value}"));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let args = ["--dry-run", "--dry-run=a-b", "--no-dry-run", "--no-no-cache"];
+    /// let tool = parse_slice::<Tool>(&args).unwrap();
+    /// assert_eq!(tool.given, ["dry-run=true", "dry-run=a-b", "dry-run=false", "no-cache=false"]);
+    ///
+    /// let error = parse_slice::<Tool>(&["--no-dry-run=x"]).err().unwrap();
+    /// assert_eq!(error.to_string(), "at argument 1: Flag 'no-dry-run' was given argument 'x' without using it");
+    /// ```
+    fn allow_negation(&self) -> bool {
+        false
+    }
+
+    /// Handle a negated long flag `--no-<long>`, if [`allow_negation`](Self::allow_negation).
+    /// By default this calls [`long`](Self::long) with the value `false`, which boolean flags
+    /// may use or ignore.
+    fn long_negated(&mut self, long: &str) -> Result<(), ParseError> {
+        let of = ValueOf::Long {
+            prefix: "--no-",
+            long,
+        };
+        self.long(long, &mut Attached::new("false", of))
+    }
+
+    /// Whether long flags may start with a single `-`. [`LongPrefixMode::DoubleDash`] by default.
+    fn long_prefix_mode(&self) -> LongPrefixMode {
+        LongPrefixMode::DoubleDash
+    }
+
+    /// What separates a flag from a value attached to it, as in `--flag=value` and
+    /// `-f=value`. Only the first separator splits, so later ones are part of the value.
+    /// `=` by default.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     values: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn value_separator(&self) -> char {
+    ///         ':'
+    ///     }
+    ///
+    ///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.values.push(next.take()?);
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn short(&mut self, _short: char, _is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.values.push(next.take()?);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let args = ["--path:C:\\dir", "-o:a:b", "--eq=x", "y"];
+    /// let tool = parse_slice::<Tool>(&args).unwrap();
+    /// assert_eq!(tool.values, ["C:\\dir", "a:b", "y"]);
+    /// ```
+    fn value_separator(&self) -> char {
+        '='
+    }
+
+    /// Whether a long flag's attached value may not start with another
+    /// [`value_separator`](Self::value_separator), so that a typo like `--flag==value` is an
+    /// error instead of the value `=value`. Off by default, as `=value` is a valid value.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, parse_with, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     strict: bool,
+    ///     value: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn strict_equals(&self) -> bool {
+    ///         self.strict
+    ///     }
+    ///
+    ///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.value = next.take()?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let lenient = parse_slice::<Tool>(&["--flag==x"]).unwrap();
+    /// assert_eq!(lenient.value, "=x");
+    ///
+    /// let strict = Tool { strict: true, ..Tool::default() };
+    /// let args = ["--flag==x"].map(String::from);
+    /// let error = parse_with(strict, args.into_iter()).err().unwrap();
+    /// assert_eq!(error.to_string(), "at argument 1: Unexpected '=' after flag '--flag'");
+    /// ```
+    fn strict_equals(&self) -> bool {
+        false
+    }
+
+    /// Whether long flags must, or must not, have their values attached with
+    /// [`value_separator`](Self::value_separator), for tools that allow only one style.
+    /// [`LongValueMode::Either`] by default. Short flags are unaffected.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_with, ArgumentParser, LongValueMode, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     mode: Option<LongValueMode>,
+    ///     output: String,
+    ///     color: Option<String>,
+    ///     files: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn long_value_mode(&self) -> LongValueMode {
+    ///         self.mode.unwrap()
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "output" => self.output = next.take()?,
+    ///             // An optional value, as in `--color` or `--color=never`.
+    ///             _ => self.color = next.take().ok(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         self.files.push(arg.into());
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// let parse = |mode, args: &[&str]| {
+    ///     let tool = Tool { mode: Some(mode), ..Tool::default() };
+    ///     let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    ///     parse_with(tool, args.into_iter()).map_err(|e| e.to_string())
+    /// };
+    ///
+    /// let tool = parse(LongValueMode::RequireEquals, &["--output=a", "--color", "b"]).unwrap();
+    /// assert_eq!((tool.output.as_str(), tool.color, tool.files), ("a", None, vec!["b".into()]));
+    /// assert_eq!(
+    ///     parse(LongValueMode::RequireEquals, &["--output", "a"]).err().unwrap(),
+    ///     "at argument 1: Flag '--output' needs its value attached, as in '--output=value'"
+    /// );
+    ///
+    /// let tool = parse(LongValueMode::ForbidEquals, &["--output", "a", "--color"]).unwrap();
+    /// assert_eq!((tool.output.as_str(), tool.color), ("a", None));
+    /// assert_eq!(
+    ///     parse(LongValueMode::ForbidEquals, &["--output=a"]).err().unwrap(),
+    ///     "at argument 1: Flag '--output' does not allow '=', give its value as the next argument"
+    /// );
+    /// ```
+    fn long_value_mode(&self) -> LongValueMode {
+        LongValueMode::Either
+    }
+
+    /// A long flag that reads more arguments from [`stdin_reader`](Self::stdin_reader) and
+    /// handles them next, before the arguments after it, as in `--args-from-stdin`.
+    /// The input is split like [`parse_str`]. The flag itself is not passed to
+    /// [`long`](Self::long).
+    fn stdin_args_flag(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Where [`stdin_args_flag`](Self::stdin_args_flag) reads arguments from.
+    /// By default this is standard input, unless it is a terminal, in which case there are
+    /// no arguments rather than waiting for the user to type some.
+    fn stdin_reader(&mut self) -> Box<dyn Read> {
+        let stdin = std::io::stdin();
+        if stdin.is_terminal() {
+            Box::new(std::io::empty())
+        } else {
+            Box::new(stdin)
+        }
+    }
+
+    /// Whether long flag names should be lowercased before being handled, so `--Verbose`
+    /// is the same as `--verbose`. Values, as in `--flag=Value`, keep their case.
+    fn case_insensitive_longs(&self) -> bool {
+        false
+    }
+
+    /// Environment variables to read long flags from when they are not given, as
+    /// `(flag, variable)` pairs. Flags on the command line always win.
+    /// The value is passed to [`long`](Self::long) just before [`finish`](Self::finish);
+    /// switches may ignore it.
+    fn env_fallbacks(&self) -> &[(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// A file of defaults for long flags, read before the arguments so that flags on the
+    /// command line win. Each line is `flag = value`, with the flag's name without its `--`,
+    /// and is handled in order as if given as `--flag=value`, so aliases, abbreviations,
+    /// [`multi_value_flags`](Self::multi_value_flags) and deprecation warnings all apply.
+    /// Switches may leave the value unused, as in `verbose = true`. Blank lines and lines
+    /// starting with `#` are skipped; a flag that fails does so with
+    /// [`ParseError::ConfigEntry`].
+    ///
+    /// Flags from the file count as given for [`required`](Self::required), but are not
+    /// duplicates of the same flag on the command line, and
+    /// [`env_fallbacks`](Self::env_fallbacks) still override them.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use krh_args_parser::{parse_with, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     config: Option<PathBuf>,
+    ///     verbose: bool,
+    ///     output: String,
+    ///     jobs: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn config_file(&self) -> Option<PathBuf> {
+    ///         self.config.clone()
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "verbose" => self.verbose = true,
+    ///             "output" => self.output = next.take()?,
+    ///             "jobs" => self.jobs = next.take()?,
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let name = format!("krh_args_parser_config_file_example_{}", std::process::id());
+    /// let path = std::env::temp_dir().join(name);
+    /// let parse = |contents: &str, args: &[&str]| {
+    ///     std::fs::write(&path, contents).unwrap();
+    ///     let tool = Tool { config: Some(path.clone()), ..Tool::default() };
+    ///     let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    ///     parse_with(tool, args.into_iter())
+    /// };
+    ///
+    /// let config = "# defaults\noutput = out.txt\n\njobs = 4\nverbose = true\n";
+    /// let tool = parse(config, &["--output", "cli.txt"]).unwrap();
+    /// assert_eq!((tool.output.as_str(), tool.jobs.as_str()), ("cli.txt", "4"));
+    /// assert!(tool.verbose);
+    ///
+    /// let error = parse("jobs = 4\noutput\n", &["--jobs=2"]).err().unwrap();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     format!("Error in config file '{}' on line 2: Expected 'flag = value'", path.display())
+    /// );
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    fn config_file(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Long flags that must be given, without their `--`. Any that were not, even through
+    /// [`env_fallbacks`](Self::env_fallbacks) or [`config_file`](Self::config_file), fail
+    /// with [`ParseError::MissingFlag`] before [`finish`](Self::finish).
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     output: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn required(&self) -> &[&'static str] {
+    ///         &["output"]
+    ///     }
+    ///
+    ///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.output = next.take()?;
+    ///         Ok(())
+    ///     }
+    ///     # fn allow_no_args(&self) -> bool { true }
+    /// }
+    ///
+    /// assert_eq!(parse_slice::<Tool>(&["--output", "out.txt"]).unwrap().output, "out.txt");
+    /// assert_eq!(
+    ///     parse_slice::<Tool>(&[]).err(),
+    ///     Some(ParseError::MissingFlag { flag: "--output".into() })
+    /// );
+    /// ```
+    fn required(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Groups of long flags, without their `--`, of which at most one may be given.
+    /// Giving two fails with [`ParseError::ExclusiveFlags`] before [`finish`](Self::finish).
+    /// Flags read from [`env_fallbacks`](Self::env_fallbacks) do not count.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool;
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn exclusive_groups(&self) -> &[&[&'static str]] {
+    ///         &[&["fast", "slow"]]
+    ///     }
+    ///
+    ///     fn long(&mut self, _long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert!(parse_slice::<Tool>(&["--fast"]).is_ok());
+    /// assert_eq!(
+    ///     parse_slice::<Tool>(&["--slow", "--verbose", "--fast"]).err().unwrap().to_string(),
+    ///     "Flags '--fast' and '--slow' are mutually exclusive"
+    /// );
+    /// ```
+    fn exclusive_groups(&self) -> &[&[&'static str]] {
+        &[]
+    }
+
+    /// The most positional arguments [`argument`](Self::argument) may accept. One more
+    /// fails with [`ParseError::TooManyArguments`] once `argument` returns, since only then
+    /// is it known not to start a subcommand.
+    /// Subcommands, `key=value` arguments and values taken by a handler do not count.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Copy {
+    ///     files: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Copy {
+    ///     fn min_positionals(&self) -> usize {
+    ///         2
+    ///     }
+    ///
+    ///     fn max_positionals(&self) -> Option<usize> {
+    ///         Some(3)
+    ///     }
+    ///
+    ///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         self.files.push(arg.into());
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(
+    ///     parse_slice::<Copy>(&["a"]).err(),
+    ///     Some(ParseError::TooFewArguments { min: 2, got: 1 })
+    /// );
+    /// assert_eq!(parse_slice::<Copy>(&["a", "b"]).unwrap().files, ["a", "b"]);
+    /// assert_eq!(parse_slice::<Copy>(&["a", "b", "c"]).unwrap().files, ["a", "b", "c"]);
+    /// assert_eq!(
+    ///     parse_slice::<Copy>(&["a", "b", "c", "d"]).err().unwrap().to_string(),
+    ///     "at argument 4: Too many arguments (expected at most 3)"
+    /// );
+    /// ```
+    fn max_positionals(&self) -> Option<usize> {
+        None
+    }
+
+    /// The fewest positional arguments [`argument`](Self::argument) must be given, counted
+    /// as for [`max_positionals`](Self::max_positionals). Fewer fail with
+    /// [`ParseError::TooFewArguments`] before [`finish`](Self::finish).
+    fn min_positionals(&self) -> usize {
+        0
+    }
+
+    /// Handle a short flag. By default every short flag is declined with
+    /// [`ParseError::Unhandled`].
+    /// If `is_last` is false, taking a value consumes the rest of the cluster, as in `-ofile`
+    /// or `-DKEY=VALUE`.
+    ///
+    /// An `=` binds to the flag just before it, and everything after it is that flag's value:
+    /// - `-vf=x` handles `v`, then `f` with the value `x`.
+    /// - `-v=f` handles `v` with the value `f`; `f` is not a flag here.
+    /// - `-v=` handles `v` with an empty value.
+    /// - `-=x` has no flag for the value, and fails with [`ParseError::EmptyFlag`].
+    ///
+    /// A flag given an `=` value is always `is_last`, and must take it or parsing fails with
+    /// [`ParseError::UnexpectedValue`]. A flag earlier in the cluster may still take the
+    /// rest of it, so `-of=x` gives `o` the value `f=x`, and `-D=KEY=VALUE` is the same as
+    /// `-DKEY=VALUE`.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// // `o` takes a value, and every other flag is a switch.
+    /// #[derive(Default)]
+    /// struct Flags(Vec<String>);
+    ///
+    /// impl ArgumentParser for Flags {
+    ///     fn short(&mut self, short: char, _is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match short {
+    ///             'o' => self.0.push(format!("o:{}", next.take()?)),
+    ///             _ => self.0.push(short.into()),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let flags = |arg| parse_slice::<Flags>(&[arg]).map(|flags| flags.0);
+    /// assert_eq!(flags("-vo=x").unwrap(), ["v", "o:x"]);
+    /// assert_eq!(flags("-vox").unwrap(), ["v", "o:x"]);
+    /// assert_eq!(flags("-vo=").unwrap(), ["v", "o:"]);
+    /// assert_eq!(flags("-o=v=x").unwrap(), ["o:v=x"]);
+    /// assert_eq!(flags("-ov=x").unwrap(), ["o:v=x"]);
+    /// assert_eq!(flags("-o==x").unwrap(), ["o:=x"]);
+    ///
+    /// // Flags are whole characters, however many bytes they take.
+    /// assert_eq!(flags("-ñéo=ü").unwrap(), ["ñ", "é", "o:ü"]);
+    /// assert_eq!(flags("-oñ=ü").unwrap(), ["o:ñ=ü"]);
+    /// assert_eq!(flags("-😀😀").unwrap(), ["😀", "😀"]);
+    /// assert_eq!(
+    ///     flags("-ñ=x").unwrap_err().to_string(),
+    ///     "at argument 1: Flag 'ñ' was given argument 'x' without using it"
+    /// );
+    /// assert_eq!(
+    ///     flags("-vf=x").unwrap_err().to_string(),
+    ///     "at argument 1: Flag 'f' was given argument 'x' without using it"
+    /// );
+    /// assert_eq!(
+    ///     flags("-=x").unwrap_err().to_string(),
+    ///     "at argument 1: Empty flag name in '-=x'"
+    /// );
+    /// ```
+    fn short(&mut self, short: char, is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+        let _ = (short, is_last, next);
+        Err(ParseError::Unhandled)
+    }
+
+    /// Handle the repeats of a short flag straight after it in a cluster, like the `vv` of
+    /// `-vvv`, once [`short`](Self::short) has handled the first without taking a value.
+    /// A flag that does take one gets the rest of the cluster as usual, as in `-oofile`.
+    /// `is_last` is whether the repeats end the cluster.
+    ///
+    /// By default this calls `short` `count` times, so a verbosity counter works without
+    /// implementing it. Repeats cannot take values.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     verbosity: usize,
+    ///     output: Option<String>,
+    ///     calls: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn short(&mut self, short: char, is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.calls.push(format!("{short} {is_last}"));
+    ///         match short {
+    ///             'v' => self.verbosity += 1,
+    ///             'o' => self.output = Some(next.take()?),
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["-vvv"]).unwrap();
+    /// assert_eq!(tool.verbosity, 3);
+    /// assert_eq!(tool.calls, ["v false", "v false", "v true"]);
+    ///
+    /// let tool = parse_slice::<Tool>(&["-oofile"]).unwrap();
+    /// assert_eq!(tool.output.as_deref(), Some("ofile"));
+    /// assert_eq!(parse_slice::<Tool>(&["-oo"]).unwrap().output.as_deref(), Some("o"));
+    /// assert_eq!(parse_slice::<Tool>(&["-vvo", "x"]).unwrap().verbosity, 2);
+    /// ```
+    ///
+    /// Implementing it sees a run as one call:
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     calls: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn short(&mut self, short: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.calls.push(format!("{short}"));
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn short_repeated(&mut self, short: char, count: usize, is_last: bool) -> Result<(), ParseError> {
+    ///         self.calls.push(format!("{short} x{count} {is_last}"));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["-vvvq", "-a"]).unwrap();
+    /// assert_eq!(tool.calls, ["v", "v x2 false", "q", "a"]);
+    /// assert_eq!(parse_slice::<Tool>(&["-qvv"]).unwrap().calls, ["q", "v", "v x1 true"]);
+    /// ```
+    fn short_repeated(
         &mut self,
         short: char,
+        count: usize,
         is_last: bool,
-        next: &mut dyn FnMut() -> Result<String, Cow<'static, str>>,
-    ) -> Result<(), Cow<'static, str>>;
+    ) -> Result<(), ParseError> {
+        let mut next = NoValue(ParseError::ValueMidCluster {
+            flag: format!("-{short}"),
+        });
+        for i in 1..=count {
+            self.short(short, is_last && i == count, &mut next)?;
+        }
+        Ok(())
+    }
 
-    /// Handle a positional argument.
+    /// The most flags a single short flag cluster like `-abc` may hold, counting repeats.
+    /// Longer clusters fail with [`ParseError::ShortChainTooLong`] before the extra flags
+    /// are handled. Unbounded by default.
+    fn max_short_chain(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Like [`unknown_long`](Self::unknown_long), for a short flag that
+    /// [`short`](Self::short) or [`short_repeated`](Self::short_repeated) declined.
+    /// The rest of the cluster is still handled, so `-xy` with an unknown `x` goes on to `y`.
+    fn unknown_short(&mut self, short: char) -> Result<(), ParseError> {
+        let _ = short;
+        Err(ParseError::Unhandled)
+    }
+
+    /// Whether arguments starting with `+` are flags, handled by [`plus`](Self::plus).
+    /// A lone `+` is still a positional argument.
+    fn allow_plus_flags(&self) -> bool {
+        false
+    }
+
+    /// Handle a `+` flag, if [`allow_plus_flags`](Self::allow_plus_flags).
+    /// Clusters like `+ab` work the same as short flag clusters, but repeats are not counted.
+    fn plus(&mut self, flag: char, is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+        let _ = (is_last, next);
+        Err(unknown_flag(&format!("+{flag}")).into())
+    }
+
+    /// Whether Windows-style `/flag` and `/flag:value` arguments are long flags, handled by
+    /// [`long`](Self::long) like `--flag` and `--flag=value`.
+    ///
+    /// Arguments whose flag name contains another `/`, like `/path/to/file`, are still
+    /// positional, as is a lone `/`. A single-component absolute path like `/tmp` cannot be
+    /// told apart from a flag, so pass it after `--` or as a relative path.
+    fn allow_slash_flags(&self) -> bool {
+        false
+    }
+
+    /// Handle a positional argument, which by default is declined with
+    /// [`ParseError::Unhandled`].
     /// return true to retry as a subcommand, giving the rest of the arguments as that of the subcommand.
-    fn argument(
-        &mut self,
-        arg: &str,
-        next: &mut dyn FnMut() -> Result<String, Cow<'static, str>>,
-    ) -> Result<bool, Cow<'static, str>>;
+    ///
+    /// A lone `-`, which usually means standard input, is a positional rather than a flag.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Cat {
+    ///     files: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Cat {
+    ///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         self.files.push(arg.into());
+    ///         Ok(false)
+    ///     }
+    ///
+    ///     fn short(&mut self, short: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         Err(format!("Unknown flag '-{short}'").into())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(parse_slice::<Cat>(&["a.txt", "-"]).unwrap().files, ["a.txt", "-"]);
+    /// ```
+    fn argument(&mut self, arg: &str, next: &mut dyn Next) -> Result<bool, ParseError> {
+        let _ = (arg, next);
+        Err(ParseError::Unhandled)
+    }
+
+    /// Whether tokens that look like negative numbers (e.g. `-5`, `-3.14`, `-5e3`) should be
+    /// accepted as values and positionals instead of being treated as short flags.
+    fn allow_negative_numbers(&self) -> bool {
+        false
+    }
+
+    /// A short flag that `-<digits>` is a shortcut for, like `head -5` for `head -n 5`.
+    /// The digits are passed to [`short`](Self::short) as the flag's value, the same as
+    /// `-n=5`. The shortcut wins over
+    /// [`allow_negative_numbers`](Self::allow_negative_numbers), but only whole arguments
+    /// are rewritten, so `--lines -5` still has the value `-5` there.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Head {
+    ///     lines: usize,
+    /// }
+    ///
+    /// impl ArgumentParser for Head {
+    ///     fn numeric_short_shortcut(&self) -> Option<char> {
+    ///         Some('n')
+    ///     }
+    ///
+    ///     fn allow_negative_numbers(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn short(&mut self, short: char, _is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match short {
+    ///             'n' => self.lines = krh_args_parser::parse_value(next)?,
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(parse_slice::<Head>(&["-5"]).unwrap().lines, 5);
+    /// assert_eq!(parse_slice::<Head>(&["-n", "12"]).unwrap().lines, 12);
+    /// assert!(parse_slice::<Head>(&["-5.0"]).is_err());
+    /// ```
+    fn numeric_short_shortcut(&self) -> Option<char> {
+        None
+    }
+
+    /// A character that marks an argument as a plain positional, so with `Some('\\')` the
+    /// argument `\-x` is passed to [`argument`](Self::argument) as `-x`. Unlike `--`, this
+    /// only affects the one argument. The escape is removed once, so `\\x` gives `\x`.
+    ///
+    /// After `--` nothing is removed. [`parse_str`] removes backslashes itself, so there
+    /// write the argument quoted, as in `'\-x'`.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, parse_str, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     args: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn positional_escape(&self) -> Option<char> {
+    ///         Some('\\')
+    ///     }
+    ///
+    ///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         self.args.push(arg.into());
+    ///         Ok(false)
+    ///     }
+    ///
+    ///     fn short(&mut self, short: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.args.push(format!("flag {short}"));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["\\-x", "-y", "--", "\\-z"]).unwrap();
+    /// assert_eq!(tool.args, ["-x", "flag y", "\\-z"]);
+    /// assert_eq!(parse_str::<Tool>(r"'\-x'").unwrap().args, ["-x"]);
+    /// ```
+    fn positional_escape(&self) -> Option<char> {
+        None
+    }
+
+    /// Whether positional arguments like `VAR=value` go to [`key_value`](Self::key_value)
+    /// instead of [`argument`](Self::argument), as in `make VAR=value target`.
+    /// Only the first `=` separates, so `VAR=a=b` has the value `a=b`. Arguments starting
+    /// with `=` and arguments after `--` are still plain positionals.
+    fn positional_keyvals(&self) -> bool {
+        false
+    }
+
+    /// Handle a `key=value` positional, if [`positional_keyvals`](Self::positional_keyvals).
+    fn key_value(&mut self, key: &str, value: &str) -> Result<(), ParseError> {
+        Err(format!("Unexpected argument '{key}={value}'").into())
+    }
+
+    /// Whether the arguments after `--` go to [`passthrough`](Self::passthrough) in groups,
+    /// for wrappers that forward them to nested programs, as in `run -- app -- more`. Each
+    /// further `--` starts a new group instead of being an argument.
+    fn passthrough_groups(&self) -> bool {
+        false
+    }
+
+    /// Handle a group of arguments after a `--`, if
+    /// [`passthrough_groups`](Self::passthrough_groups). The first `--` starts group 0.
+    /// Groups are handled in order once the arguments before them have been, and may be
+    /// empty.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Run {
+    ///     groups: Vec<Vec<String>>,
+    /// }
+    ///
+    /// impl ArgumentParser for Run {
+    ///     fn passthrough_groups(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn passthrough(&mut self, group: usize, args: Vec<String>) -> Result<(), ParseError> {
+    ///         assert_eq!(group, self.groups.len());
+    ///         self.groups.push(args);
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn argument(&mut self, _arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// let run = parse_slice::<Run>(&["a", "--", "b", "c", "--", "d"]).unwrap();
+    /// assert_eq!(run.groups, [vec!["b", "c"], vec!["d"]]);
+    /// ```
+    fn passthrough(&mut self, group: usize, args: Vec<String>) -> Result<(), ParseError> {
+        let _ = group;
+        match args.first() {
+            Some(arg) => Err(format!("Unexpected argument '{arg}'").into()),
+            None => Ok(()),
+        }
+    }
+
+    /// An argument after which every argument goes to [`rest`](Self::rest) instead of being
+    /// parsed, like `--` but named, as in `tool exec cmd -x`. It is matched exactly, so may
+    /// be a flag like `--exec` or a word like `exec`, and is checked before flags, positionals
+    /// and subcommands. It has no effect after `--`, or once a subcommand has been found.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     verbose: bool,
+    ///     command: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn capture_rest_after(&self) -> Option<&'static str> {
+    ///         Some("exec")
+    ///     }
+    ///
+    ///     fn rest(&mut self, args: &mut dyn Iterator<Item = String>) -> Result<(), ParseError> {
+    ///         self.command.extend(args);
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn short(&mut self, _short: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.verbose = true;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["-v", "exec", "a", "-b", "c"]).unwrap();
+    /// assert!(tool.verbose);
+    /// assert_eq!(tool.command, ["a", "-b", "c"]);
+    ///
+    /// let tool = parse_slice::<Tool>(&["exec"]).unwrap();
+    /// assert!(tool.command.is_empty());
+    /// ```
+    fn capture_rest_after(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Handle the arguments after [`capture_rest_after`](Self::capture_rest_after), which
+    /// may be none. Any left in `args` are ignored.
+    fn rest(&mut self, args: &mut dyn Iterator<Item = String>) -> Result<(), ParseError> {
+        match args.next() {
+            Some(arg) => Err(format!("Unexpected argument '{arg}'").into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle a positional argument that may not be valid UTF-8.
+    /// This is only called with the raw argument by [`parse_os`]; by default it is converted
+    /// lossily and passed to [`argument`](Self::argument).
+    fn argument_os(&mut self, arg: &OsStr, next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.argument(&arg.to_string_lossy(), next)
+    }
+
+    /// The names of subcommands. If the first positional argument is one of these, it is
+    /// passed straight to [`subcommand`](Self::subcommand) without calling
+    /// [`argument`](Self::argument). Returning `true` from `argument` also still works.
+    ///
+    /// Like long flags, subcommands may be abbreviated to any unique prefix, and
+    /// `subcommand` gets the full name. An exact match always wins, and a prefix of more
+    /// than one fails with [`ParseError::AmbiguousSubcommand`]. Any other first positional
+    /// goes to `argument`, and if that declines it with [`ParseError::Unhandled`], parsing
+    /// fails with [`ParseError::UnknownSubcommand`], suggesting a subcommand at most two
+    /// edits away.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, ParseError};
+    /// #[derive(Default)]
+    /// struct Git {
+    ///     command: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Git {
+    ///     fn subcommands(&self) -> &[&'static str] {
+    ///         &["status", "commit", "checkout"]
+    ///     }
+    ///
+    ///     fn subcommand(
+    ///         &mut self,
+    ///         command: &str,
+    ///         _command_args: Box<dyn Iterator<Item = String>>,
+    ///     ) -> Result<(), ParseError> {
+    ///         self.command = command.into();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let command = |arg| parse_slice::<Git>(&[arg]).map(|git| git.command);
+    /// assert_eq!(command("st").unwrap(), "status");
+    /// assert_eq!(command("commit").unwrap(), "commit");
+    /// assert_eq!(
+    ///     command("c").unwrap_err().to_string(),
+    ///     "at argument 1: Subcommand 'c' is ambiguous, could be any of: commit, checkout"
+    /// );
+    /// assert_eq!(
+    ///     command("stauts").unwrap_err().to_string(),
+    ///     "at argument 1: Unknown subcommand 'stauts'; did you mean 'status'?"
+    /// );
+    /// assert_eq!(
+    ///     command("push").unwrap_err().to_string(),
+    ///     "at argument 1: Unknown subcommand 'push'"
+    /// );
+    /// ```
+    fn subcommands(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Whether parsing stops at a subcommand, which is always the first positional argument,
+    /// and hands it every argument after it. Other positionals never stop parsing.
+    ///
+    /// When off, this parser keeps handling flags after the subcommand. Flags it declines
+    /// with [`ParseError::Unhandled`], positionals, and everything after a `--` are passed
+    /// on in order, and the subcommand is handled once the arguments run out.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     intermix: bool,
+    ///     verbose: bool,
+    ///     build_args: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn stop_at_first_positional(&self) -> bool {
+    ///         !self.intermix
+    ///     }
+    ///
+    ///     fn subcommands(&self) -> &[&'static str] {
+    ///         &["build"]
+    ///     }
+    ///
+    ///     fn subcommand(
+    ///         &mut self,
+    ///         _command: &str,
+    ///         command_args: Box<dyn Iterator<Item = String>>,
+    ///     ) -> Result<(), ParseError> {
+    ///         self.build_args = command_args.collect();
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "verbose" => self.verbose = true,
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let args = ["build", "--verbose", "--release", "src"];
+    /// let tool = parse_slice::<Tool>(&args).unwrap();
+    /// assert!(!tool.verbose);
+    /// assert_eq!(tool.build_args, ["--verbose", "--release", "src"]);
+    ///
+    /// let mut tool = Tool::default();
+    /// tool.intermix = true;
+    /// let tool = krh_args_parser::parse_with(tool, args.map(String::from).into_iter()).unwrap();
+    /// assert!(tool.verbose);
+    /// assert_eq!(tool.build_args, ["--release", "src"]);
+    /// ```
+    fn stop_at_first_positional(&self) -> bool {
+        true
+    }
 
     /// Handle a subcommand with the given arguments.
+    /// It is called even if no arguments follow the subcommand, with an empty iterator,
+    /// unless [`subcommand_requires_args`](Self::subcommand_requires_args) is on.
+    /// Only called through [`subcommand_iter`](Self::subcommand_iter), so it is not called
+    /// at all if that is overridden.
     fn subcommand(
         &mut self,
         command: &str,
         command_args: Box<dyn Iterator<Item = String>>,
-    ) -> Result<(), Cow<'static, str>>;
+    ) -> Result<(), ParseError> {
+        let _ = (command, command_args);
+        Err(ParseError::Unhandled)
+    }
+
+    /// Handle a subcommand with the given arguments, without boxing them.
+    /// By default this boxes them for [`subcommand`](Self::subcommand), which costs one
+    /// allocation per parse that reaches a subcommand; overriding this saves it.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     command: String,
+    ///     args: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn subcommands(&self) -> &[&'static str] {
+    ///         &["run"]
+    ///     }
+    ///
+    ///     fn subcommand_iter<I>(&mut self, command: &str, command_args: I) -> Result<(), ParseError>
+    ///     where
+    ///         I: Iterator<Item = String> + 'static,
+    ///     {
+    ///         self.command = command.into();
+    ///         self.args.extend(command_args);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["run", "--fast", "a", "--", "b"]).unwrap();
+    /// assert_eq!(tool.command, "run");
+    /// assert_eq!(tool.args, ["--fast", "a", "--", "b"]);
+    /// ```
+    fn subcommand_iter<I>(&mut self, command: &str, command_args: I) -> Result<(), ParseError>
+    where
+        I: Iterator<Item = String> + 'static,
+    {
+        self.subcommand(command, Box::new(command_args))
+    }
+
+    /// Whether `command` fails with [`ParseError::EmptySubcommand`] when no arguments follow
+    /// it, instead of [`subcommand`](Self::subcommand) getting none.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, ParseError};
+    /// #[derive(Default)]
+    /// struct Git {
+    ///     command: String,
+    ///     args: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Git {
+    ///     fn subcommands(&self) -> &[&'static str] {
+    ///         &["status", "add"]
+    ///     }
+    ///
+    ///     fn subcommand_requires_args(&self, command: &str) -> bool {
+    ///         command == "add"
+    ///     }
+    ///
+    ///     fn subcommand(
+    ///         &mut self,
+    ///         command: &str,
+    ///         command_args: Box<dyn Iterator<Item = String>>,
+    ///     ) -> Result<(), ParseError> {
+    ///         self.command = command.into();
+    ///         self.args = command_args.collect();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let git = parse_slice::<Git>(&["status"]).unwrap();
+    /// assert_eq!(git.command, "status");
+    /// assert!(git.args.is_empty());
+    ///
+    /// assert_eq!(parse_slice::<Git>(&["add", "."]).unwrap().args, ["."]);
+    /// assert_eq!(
+    ///     parse_slice::<Git>(&["add"]).err().unwrap().to_string(),
+    ///     "at argument 1: Subcommand 'add' needs arguments"
+    /// );
+    /// ```
+    fn subcommand_requires_args(&self, command: &str) -> bool {
+        let _ = command;
+        false
+    }
+
+    /// Called once [`subcommand`](Self::subcommand) has handled `command` without error,
+    /// before [`finish`](Self::finish), for finishing off the parent's own state.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     log: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn subcommands(&self) -> &[&'static str] {
+    ///         &["build"]
+    ///     }
+    ///
+    ///     fn subcommand(
+    ///         &mut self,
+    ///         command: &str,
+    ///         _command_args: Box<dyn Iterator<Item = String>>,
+    ///     ) -> Result<(), ParseError> {
+    ///         self.log.push(format!("ran {command}"));
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn after_subcommand(&mut self, command: &str) -> Result<(), ParseError> {
+    ///         self.log.push(format!("after {command}"));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["build", "--release"]).unwrap();
+    /// assert_eq!(tool.log, ["ran build", "after build"]);
+    /// ```
+    fn after_subcommand(&mut self, command: &str) -> Result<(), ParseError> {
+        let _ = command;
+        Ok(())
+    }
+
+    /// Parse a subcommand's arguments with its own parser, from within
+    /// [`subcommand`](Self::subcommand). The parent keeps its already-parsed state, so it can
+    /// store the typed result alongside its global flags (or hand them to it).
+    /// `command` is given to the sub-parser as its [`program_name`](Self::program_name).
+    ///
+    /// Unlike [`parse`], a subcommand given no arguments is not an error.
+    ///
+    /// ```
+    /// # use krh_args_parser::{ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     verbose: bool,
+    ///     build: Option<Build>,
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Build {
+    ///     release: bool,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn subcommands(&self) -> &[&'static str] {
+    ///         &["build"]
+    ///     }
+    ///
+    ///     fn subcommand(
+    ///         &mut self,
+    ///         command: &str,
+    ///         command_args: Box<dyn Iterator<Item = String>>,
+    ///     ) -> Result<(), ParseError> {
+    ///         self.build = Some(self.subcommand_parse(command, command_args)?);
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "verbose" => self.verbose = true,
+    ///             _ => return Err(format!("Unknown flag '--{long}'").into()),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// impl ArgumentParser for Build {
+    ///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "release" => self.release = true,
+    ///             _ => return Err(format!("Unknown flag '--{long}'").into()),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let args = ["--verbose", "build", "--release"].map(String::from);
+    /// let tool: Tool = krh_args_parser::parse(args.into_iter()).unwrap();
+    /// assert!(tool.verbose && tool.build.unwrap().release);
+    /// ```
+    fn subcommand_parse<S: ArgumentParser>(
+        &mut self,
+        command: &str,
+        command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<S, ParseError> {
+        let mut parser = S::default();
+        parser.program_name(command);
+        parse_command(parser, command_args, false).map(|(parser, _)| parser)
+    }
+
+    /// Describe the command's flags and subcommands, for [`completions`] and [`man_page`].
+    /// By default this lists [`known_longs`](Self::known_longs) and
+    /// [`subcommands`](Self::subcommands) with no name, help or values, so override it to
+    /// give the full picture.
+    fn describe(&self) -> CommandSpec {
+        CommandSpec {
+            flags: self
+                .known_longs()
+                .iter()
+                .map(|&long| FlagSpec::long(long))
+                .collect(),
+            subcommands: self
+                .subcommands()
+                .iter()
+                .map(|&name| CommandSpec::new(name))
+                .collect(),
+            ..CommandSpec::default()
+        }
+    }
+
+    /// Checked after each argument is handled. Returning true stops parsing successfully,
+    /// returning the parser without looking at the rest of the arguments or calling
+    /// [`finish`](Self::finish). [`parse_partial`] returns the arguments that were left.
+    ///
+    /// This suits flags like a custom `--version`, which should win even if a later
+    /// argument is invalid.
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    /// Called once all arguments have been handled, including after a subcommand.
+    /// Use this to check required arguments or fill in defaults.
+    fn finish(&mut self) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+pub fn parse_args<P: ArgumentParser>() -> Result<P, ParseError> {
+    parse_from(std::env::args())
+}
+
+/// Like [`parse_args`], but reads the process arguments with [`std::env::args_os`], so they
+/// need not be valid UTF-8. See [`parse_os`].
+pub fn parse_args_os<P: ArgumentParser>() -> Result<P, ParseError> {
+    let mut args = std::env::args_os();
+    // The first argument is always the program name.
+    let name = args.next().unwrap_or_default();
+    let mut parser = P::default();
+    parser.program_name(&name.to_string_lossy());
+    parse_into(parser, args)
+}
+
+/// Whether help and other output should be styled: standard output is a terminal, and the
+/// `NO_COLOR` environment variable is not set.
+pub fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
 }
 
-pub fn parse_args<P: ArgumentParser>() -> Result<P, Cow<'static, str>> {
-    // Skip the first argument, which is always the program name.
-    parse(std::env::args().skip(1))
+/// Like [`parse_args`], but reads from `args` instead of the process arguments.
+/// The first item is taken as the program name.
+pub fn parse_from<P: ArgumentParser>(
+    mut args: impl Iterator<Item = String> + 'static,
+) -> Result<P, ParseError> {
+    // The first argument is always the program name.
+    let name = args.next().unwrap_or_default();
+    parse_with_name(&name, args)
 }
 
+/// Like [`parse`], but first hands `name` to [`ArgumentParser::program_name`].
+pub fn parse_with_name<P: ArgumentParser>(
+    name: &str,
+    args: impl Iterator<Item = String> + 'static,
+) -> Result<P, ParseError> {
+    let mut parser = P::default();
+    parser.program_name(name);
+    parse_with(parser, args)
+}
+
+/// Parse the given arguments, which should not include the program name.
+/// [`ArgumentParser::program_name`] is not called; use [`parse_with_name`] to provide one.
+///
+/// A standalone `--` ends option parsing: it is consumed, and every token after it
+/// (including any further `--`) is passed verbatim to [`ArgumentParser::argument`].
+///
+/// With the `tracing` feature, each argument and the flag, positional or subcommand it is
+/// handled as are logged at the trace level, for finding out why a handler is not reached.
+///
+/// ```
+/// # #[cfg(feature = "tracing")] {
+/// # use std::sync::{Arc, Mutex};
+/// # use krh_args_parser::parse_slice;
+/// use tracing::field::{Field, Visit};
+/// use tracing::span::{Attributes, Id, Record};
+/// use tracing::{Event, Metadata};
+///
+/// #[derive(Default)]
+/// struct Events(Arc<Mutex<Vec<String>>>);
+///
+/// struct Line(String);
+///
+/// impl Visit for Line {
+///     fn record_str(&mut self, field: &Field, value: &str) {
+///         self.record_debug(field, &format_args!("{value}"));
+///     }
+///
+///     fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+///         match field.name() {
+///             "message" => self.0.insert_str(0, &format!("{value:?}")),
+///             name => self.0.push_str(&format!(" {name}={value:?}")),
+///         }
+///     }
+/// }
+///
+/// impl tracing::Subscriber for Events {
+///     fn event(&self, event: &Event<'_>) {
+///         let mut line = Line(String::new());
+///         event.record(&mut line);
+///         self.0.lock().unwrap().push(line.0);
+///     }
+///     fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+///     fn new_span(&self, _: &Attributes<'_>) -> Id { Id::from_u64(1) }
+///     fn record(&self, _: &Id, _: &Record<'_>) {}
+///     fn record_follows_from(&self, _: &Id, _: &Id) {}
+///     fn enter(&self, _: &Id) {}
+///     fn exit(&self, _: &Id) {}
+/// }
+/// # #[derive(Default)]
+/// # struct Tool;
+/// # impl krh_args_parser::ArgumentParser for Tool {
+/// #     fn long(&mut self, _: &str, next: &mut dyn krh_args_parser::Next) -> Result<(), krh_args_parser::ParseError> { next.take()?; Ok(()) }
+/// #     fn short(&mut self, _: char, _: bool, _: &mut dyn krh_args_parser::Next) -> Result<(), krh_args_parser::ParseError> { Ok(()) }
+/// #     fn argument(&mut self, _: &str, _: &mut dyn krh_args_parser::Next) -> Result<bool, krh_args_parser::ParseError> { Ok(false) }
+/// # }
+///
+/// let events = Events::default();
+/// let log = events.0.clone();
+/// tracing::subscriber::with_default(events, || {
+///     parse_slice::<Tool>(&["-v", "--out", "file", "src"]).unwrap();
+/// });
+/// assert_eq!(
+///     *log.lock().unwrap(),
+///     [
+///         "argument index=1 arg=-v",
+///         "flag name=-v",
+///         "argument index=2 arg=--out",
+///         "flag name=--out",
+///         "argument index=4 arg=src",
+///         "positional arg=src",
+///     ]
+/// );
+/// # }
+/// ```
 pub fn parse<P: ArgumentParser>(
     args: impl Iterator<Item = String> + 'static,
-) -> Result<P, Cow<'static, str>> {
+) -> Result<P, ParseError> {
+    parse_with(P::default(), args)
+}
+
+/// Like [`parse`], but starts from an already-constructed parser instead of
+/// [`Default`], so callers can seed it with configuration before parsing.
+pub fn parse_with<P: ArgumentParser>(
+    parser: P,
+    args: impl Iterator<Item = String> + 'static,
+) -> Result<P, ParseError> {
+    parse_into(parser, args.map(OsString::from))
+}
+
+/// Like [`parse`], but for validating a command line without acting on it.
+/// See [`ArgumentParser::set_dry_run`].
+pub fn parse_dry_run<P: ArgumentParser>(
+    args: impl Iterator<Item = String> + 'static,
+) -> Result<P, ParseError> {
     let mut parser = P::default();
+    parser.set_dry_run(true);
+    parse_with(parser, args)
+}
 
-    let mut args = args.peekable();
+/// Like [`parse`], but always returns the parser, holding whatever the handlers set before
+/// any error. Useful for tools like editors that work with incomplete command lines.
+///
+/// ```
+/// # use krh_args_parser::{parse_lenient, ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// struct Tool {
+///     verbose: bool,
+/// }
+///
+/// impl ArgumentParser for Tool {
+///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+///         match long {
+///             "verbose" => self.verbose = true,
+///             _ => return Err(format!("Unknown flag '--{long}'").into()),
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let args = ["--verbose", "--colour"].map(String::from);
+/// let (tool, result) = parse_lenient::<Tool>(args.into_iter());
+/// assert!(tool.verbose);
+/// assert_eq!(
+///     result.unwrap_err().to_string(),
+///     "at argument 2: Unknown flag '--colour'"
+/// );
+/// ```
+pub fn parse_lenient<P: ArgumentParser>(
+    args: impl Iterator<Item = String> + 'static,
+) -> (P, Result<(), ParseError>) {
+    let (parser, result) = driver::run_lenient(P::default(), args.map(OsString::from), false);
+    (parser, result.map(|_| ()))
+}
+
+/// Like [`parse`], but takes borrowed arguments, which should not include the program name.
+///
+/// Nothing is read from the process, so this is the recommended entry point for tests:
+/// `parse_slice::<Args>(&["-v", "input"])`.
+pub fn parse_slice<P: ArgumentParser>(args: &[&str]) -> Result<P, ParseError> {
+    parse_borrowed(args.iter().copied())
+}
+
+/// Like [`parse`], but takes borrowed arguments, which need not outlive the parse.
+///
+/// Arguments are only copied when a handler takes one as a value, so flags and positionals
+/// are handled straight from `args`, and `benches/parse_borrowed.rs` shows this is faster
+/// than [`parse`] for a tool parsing in a loop. The exception is a subcommand: the iterator
+/// handed to [`ArgumentParser::subcommand_iter`] must be `'static`, so it cannot borrow
+/// from `args`, and the arguments left for it are copied into owned strings first.
+///
+/// ```
+/// # use krh_args_parser::{parse_borrowed, ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// struct Tool {
+///     verbose: bool,
+///     files: Vec<String>,
+/// }
+///
+/// impl ArgumentParser for Tool {
+///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+///         self.files.push(arg.into());
+///         Ok(false)
+///     }
+///     fn short(&mut self, _short: char, _: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+///         self.verbose = true;
+///         Ok(())
+///     }
+/// }
+///
+/// let line = String::from("-v a.txt b.txt");
+/// let tool = parse_borrowed::<Tool>(line.split(' ')).unwrap();
+/// assert!(tool.verbose);
+/// assert_eq!(tool.files, ["a.txt", "b.txt"]);
+/// ```
+pub fn parse_borrowed<'a, P: ArgumentParser>(
+    args: impl Iterator<Item = &'a str> + 'a,
+) -> Result<P, ParseError> {
+    parse_into(P::default(), args.map(OsStr::new))
+}
+
+/// Like [`parse`], but splits a whole command line into arguments first.
+///
+/// Splitting follows POSIX shell quoting: whitespace separates arguments, single quotes are
+/// literal, and double quotes and backslashes escape as usual. Nothing is expanded.
+///
+/// Quotes may start anywhere in an argument and are removed, so a quoted value attached
+/// with `=` keeps its spaces:
+///
+/// ```
+/// # use krh_args_parser::{parse_str, ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// struct Tool {
+///     values: Vec<String>,
+/// }
+///
+/// impl ArgumentParser for Tool {
+///     fn long(&mut self, _long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+///         self.values.push(next.take()?);
+///         Ok(())
+///     }
+/// }
+///
+/// let values = |line| parse_str::<Tool>(line).unwrap().values;
+/// assert_eq!(values(r#"--out="my file.txt""#), ["my file.txt"]);
+/// assert_eq!(values("--out='my file.txt'"), ["my file.txt"]);
+/// assert_eq!(values(r#"--out=a" "b"#), ["a b"]);
+/// assert_eq!(values(r#"--out='a "b"'" 'c'""#), [r#"a "b" 'c'"#]);
+/// assert_eq!(values(r#"--out "a b" --in=x\ y"#), ["a b", "x y"]);
+/// ```
+pub fn parse_str<P: ArgumentParser>(line: &str) -> Result<P, ParseError> {
+    parse(split::split(line)?.into_iter())
+}
+
+/// Like [`parse`], but stops at the first argument a handler declines by returning
+/// [`ParseError::Unhandled`], returning that argument and every one after it.
+///
+/// This lets one parser handle its own flags and pass the rest on to another.
+/// The whole declined argument is returned, so a handler should decline before changing any
+/// state, and before taking any values.
+pub fn parse_partial<P: ArgumentParser>(
+    args: impl Iterator<Item = String> + 'static,
+) -> Result<(P, Vec<String>), ParseError> {
+    driver::run(P::default(), args.map(OsString::from), true)
+}
 
+/// Like [`parse`], but accepts arguments that may not be valid UTF-8.
+///
+/// Only positional arguments are passed through raw, via [`ArgumentParser::argument_os`].
+/// Flag names, flag values and the arguments given to [`ArgumentParser::subcommand`] are
+/// still strings, so any invalid UTF-8 in them is replaced with `U+FFFD`.
+pub fn parse_os<P: ArgumentParser>(
+    args: impl Iterator<Item = OsString> + 'static,
+) -> Result<P, ParseError> {
+    parse_into(P::default(), args)
+}
+
+/// Split arguments into [`ArgEvent`]s without an [`ArgumentParser`], for doing your own
+/// dispatch. The arguments should not include the program name.
+///
+/// Nothing here knows which flags take values, so only values given with `=` are attached.
+/// A value in the following argument comes through as an [`ArgEvent::Positional`], and
+/// `-ofile` is the flags `o`, `f`, `i`, `l` and `e`. A lone `-` is positional.
+///
+/// ```
+/// # use krh_args_parser::{events, ArgEvent};
+/// let args = ["--a=1", "-bc=2", "x", "--", "-y"].map(String::from);
+/// let events: Vec<_> = events(args.into_iter()).collect::<Result<_, _>>().unwrap();
+/// let value = |value: &str| Some(value.to_owned());
+/// assert_eq!(
+///     events,
+///     [
+///         ArgEvent::Long { name: "a".into(), value: value("1") },
+///         ArgEvent::Short { c: 'b', value: None },
+///         ArgEvent::Short { c: 'c', value: value("2") },
+///         ArgEvent::Positional("x".into()),
+///         ArgEvent::Separator,
+///         ArgEvent::Positional("-y".into()),
+///     ]
+/// );
+/// ```
+pub fn events(
+    args: impl Iterator<Item = String>,
+) -> impl Iterator<Item = Result<ArgEvent, ParseError>> {
+    events::Events::new(args)
+}
+
+/// Parse one level of a subcommand tree, from within [`ArgumentParser::subcommand`].
+/// Like [`parse_partial`], this stops at the first argument `S` declines with
+/// [`ParseError::Unhandled`] and returns it and the rest, for the caller to dispatch to a
+/// deeper subcommand, as in `git remote add`. Empty `args` are not an error.
+pub fn parse_subcommand<S: ArgumentParser>(
+    args: Box<dyn Iterator<Item = String>>,
+) -> Result<(S, Box<dyn Iterator<Item = String>>), ParseError> {
+    let (parser, rest) = parse_command(S::default(), args, true)?;
+    Ok((parser, Box::new(rest.into_iter())))
+}
+
+/// Run `parser` over a subcommand's arguments, which unlike a whole command line may be empty.
+fn parse_command<P: ArgumentParser>(
+    parser: P,
+    args: impl Iterator<Item = String> + 'static,
+    partial: bool,
+) -> Result<(P, Vec<String>), ParseError> {
+    let mut args = args.peekable();
     if args.peek().is_none() {
-        return Err("No arguments given".into());
-    }
-
-    while let Some(arg) = args.next() {
-        if let Some(s) = arg.strip_prefix("--") {
-            match s.split_once('=') {
-                Some((long, next)) => {
-                    let mut taken = false;
-                    let mut nextfn = || {
-                        taken = true;
-                        Ok(next.into())
-                    };
-                    parser.long(long, &mut nextfn)?;
-
-                    if !taken {
-                        return Err(format!(
-                            "Flag '{long}' was given argument '{next}' without using it"
-                        )
-                        .into());
-                    }
-                }
-                None => {
-                    let mut nextfn = || match args.peek() {
-                        Some(f) => {
-                            if f.starts_with('-') {
-                                Err(format!("Expected value, got flag {f}").into())
-                            } else {
-                                Ok(args.next().unwrap())
-                            }
-                        }
-                        None => Err("Expected value but no arguments were left".into()),
-                    };
-
-                    parser.long(s, &mut nextfn)?;
-                }
-            }
-        } else if let Some(s) = arg.strip_prefix('-') {
-            let mut peekable = s.chars().peekable();
-            while let Some(c) = peekable.next() {
-                match peekable.peek() {
-                    Some(&p) => {
-                        if p == '=' {
-                            let mut taken = false;
-                            let (_, next) = s.split_once('=').unwrap();
-                            let mut nextfn = || {
-                                taken = true;
-                                Ok(next.into())
-                            };
-                            parser.short(c, true, &mut nextfn)?;
-
-                            if !taken {
-                                return Err(format!(
-                                    "Flag '{s}' was given argument '{next}' without using it"
-                                )
-                                .into());
-                            }
-
-                            break;
-                        } else {
-                            parser.short(c, false, &mut || {
-                                Err(
-                                    format!("Could not get argument for {c} while in short chain")
-                                        .into(),
-                                )
-                            })?;
-                        }
-                    }
-                    None => {
-                        let mut nextfn = || match args.peek() {
-                            Some(f) => {
-                                if f.starts_with('-') {
-                                    Err(format!("Expected value, got flag {f}").into())
-                                } else {
-                                    Ok(args.next().unwrap())
-                                }
-                            }
-                            None => {
-                                Err(format!("Expected value for {c} but no arguments were left")
-                                    .into())
-                            }
-                        };
-                        parser.short(c, true, &mut nextfn)?;
-                    }
-                }
-            }
-        } else {
-            #[allow(clippy::collapsible_else_if)]
-            if parser.argument(&arg, &mut || args.next().ok_or("No argument next".into()))? {
-                parser.subcommand(&arg, Box::new(args))?;
-                return Ok(parser);
-            };
-        }
+        return Ok((driver::run_without_args(parser)?, Vec::new()));
     }
+    driver::run(parser, args.map(OsString::from), partial)
+}
 
-    Ok(parser)
+fn parse_into<'a, P: ArgumentParser>(
+    parser: P,
+    args: impl Iterator<Item = impl Into<Cow<'a, OsStr>> + 'a> + 'a,
+) -> Result<P, ParseError> {
+    driver::run(parser, args, false).map(|(parser, _)| parser)
 }
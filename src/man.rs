@@ -0,0 +1,93 @@
+use std::fmt::Write;
+
+use crate::{CommandSpec, FlagSpec};
+
+/// Troff source for a section 1 man page, listing the synopsis, options and subcommands
+/// in `spec`. Descriptions are escaped, so they may contain any text.
+pub fn man_page(spec: &CommandSpec) -> String {
+    let name = escape(spec.name);
+    let mut out = String::new();
+
+    writeln!(out, ".TH {} 1", escape(&spec.name.to_uppercase())).unwrap();
+    out.push_str(".SH NAME\n");
+    if spec.about.is_empty() {
+        writeln!(out, "{name}").unwrap();
+    } else {
+        writeln!(out, "{name} \\- {}", escape(spec.about)).unwrap();
+    }
+
+    out.push_str(".SH SYNOPSIS\n");
+    writeln!(out, ".B {name}").unwrap();
+    if !spec.flags.is_empty() {
+        out.push_str("[\\fIOPTIONS\\fR]\n");
+    }
+    if !spec.subcommands.is_empty() {
+        out.push_str("[\\fICOMMAND\\fR]\n");
+    }
+
+    if !spec.flags.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        flags(&mut out, &spec.flags);
+    }
+
+    if !spec.subcommands.is_empty() {
+        out.push_str(".SH COMMANDS\n");
+        subcommands(&mut out, "", &spec.subcommands);
+    }
+
+    out
+}
+
+fn flags(out: &mut String, flags: &[FlagSpec]) {
+    for flag in flags {
+        out.push_str(".TP\n");
+        let names: Vec<_> = flag
+            .names()
+            .map(|name| format!("\\fB{}\\fR", escape(&name)))
+            .collect();
+        out.push_str(&names.join(", "));
+        if flag.takes_value {
+            out.push_str(" \\fIVALUE\\fR");
+        }
+        out.push('\n');
+        let help = flag.help_text();
+        if !help.is_empty() {
+            writeln!(out, "{}", escape(&help)).unwrap();
+        }
+    }
+}
+
+/// List `commands` and their options, then their own subcommands under their full path.
+fn subcommands(out: &mut String, parent: &str, commands: &[CommandSpec]) {
+    for command in commands {
+        let path = format!("{parent}{}", command.name);
+        out.push_str(".TP\n");
+        writeln!(out, "\\fB{}\\fR", escape(&path)).unwrap();
+        if !command.about.is_empty() {
+            writeln!(out, "{}", escape(command.about)).unwrap();
+        }
+        if !command.flags.is_empty() {
+            out.push_str(".RS\n");
+            flags(out, &command.flags);
+            out.push_str(".RE\n");
+        }
+        subcommands(out, &format!("{path} "), &command.subcommands);
+    }
+}
+
+/// Escape text for troff, so backslashes and dashes are literal and a line cannot start a
+/// request.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    let lines: Vec<_> = escaped
+        .lines()
+        .map(|line| {
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
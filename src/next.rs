@@ -0,0 +1,638 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::driver::lossy;
+use crate::ParseError;
+
+/// The remaining arguments, as seen by the parse loop.
+/// Arguments given as `&str` stay borrowed until taken.
+pub(crate) struct Args<'a> {
+    /// Arguments already read from `iter`, for looking ahead.
+    ahead: VecDeque<Cow<'a, OsStr>>,
+    iter: Box<dyn Iterator<Item = Cow<'a, OsStr>> + 'a>,
+}
+
+impl<'a> Args<'a> {
+    pub(crate) fn new(iter: impl Iterator<Item = Cow<'a, OsStr>> + 'a) -> Self {
+        Self {
+            ahead: VecDeque::new(),
+            iter: Box::new(iter),
+        }
+    }
+
+    pub(crate) fn peek(&mut self) -> Option<&Cow<'a, OsStr>> {
+        self.peek_nth(0)
+    }
+
+    /// The argument `n` places after the next one.
+    fn peek_nth(&mut self, n: usize) -> Option<&Cow<'a, OsStr>> {
+        while self.ahead.len() <= n {
+            self.ahead.push_back(self.iter.next()?);
+        }
+        self.ahead.get(n)
+    }
+}
+
+impl<'a> Iterator for Args<'a> {
+    type Item = Cow<'a, OsStr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ahead.pop_front().or_else(|| self.iter.next())
+    }
+}
+
+/// Where a flag or positional argument gets its value from.
+pub trait Next {
+    /// Look at the next value without taking it.
+    /// When the value would come from the following argument, this returns it even if it
+    /// looks like a flag, so the handler can decide whether to [`take`](Self::take) it.
+    fn peek(&mut self) -> Option<Cow<'_, str>>;
+
+    /// Take the next value.
+    /// A following argument that looks like a flag is not taken, and is an error instead.
+    fn take(&mut self) -> Result<String, ParseError>;
+
+    /// Like [`take`](Self::take), but also takes a following argument that starts with `-`,
+    /// for values like `--output -` or `--regex -i`. A `--` separator is still not taken.
+    ///
+    /// Each handler picks which to call, so only the flags that want such values get them:
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Grep {
+    ///     pattern: String,
+    ///     count: String,
+    /// }
+    ///
+    /// impl ArgumentParser for Grep {
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "pattern" => self.pattern = next.take_allow_dash()?,
+    ///             "count" => self.count = next.take()?,
+    ///             _ => return Err(ParseError::Unhandled),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(parse_slice::<Grep>(&["--pattern", "-x"]).unwrap().pattern, "-x");
+    /// assert_eq!(
+    ///     parse_slice::<Grep>(&["--count", "-x"]).err().unwrap().to_string(),
+    ///     "at argument 1: Expected value, got flag -x"
+    /// );
+    /// ```
+    fn take_allow_dash(&mut self) -> Result<String, ParseError> {
+        self.take()
+    }
+
+    /// Take exactly `n` values, for flags like `--point X Y`.
+    /// Running out of values, or reaching a flag, first fails with
+    /// [`ParseError::TooFewValues`].
+    fn take_n(&mut self, n: usize) -> Result<Vec<String>, ParseError> {
+        let mut values = Vec::with_capacity(n);
+        while values.len() < n {
+            match self.take() {
+                Ok(value) => values.push(value),
+                Err(_) => {
+                    return Err(ParseError::TooFewValues {
+                        flag: self.flag(),
+                        expected: n,
+                        got: values.len(),
+                    })
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// The flag the value is for as given, like `--output` or `-o`, or `None` for a
+    /// positional argument.
+    fn flag(&self) -> Option<String> {
+        None
+    }
+
+    /// Where the value comes from, for flags that treat `-o=` with an empty value
+    /// differently from `-o` with none.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError, ValueSource};
+    /// #[derive(Default)]
+    /// struct Tool {
+    ///     sources: Vec<ValueSource>,
+    ///     output: Option<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Tool {
+    ///     fn short(&mut self, _short: char, is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         self.sources.push(next.source());
+    ///         if is_last && next.source() == ValueSource::Attached || next.peek().is_some() {
+    ///             self.output = Some(next.take()?);
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tool = parse_slice::<Tool>(&["-o="]).unwrap();
+    /// assert_eq!((tool.sources, tool.output), (vec![ValueSource::Attached], Some("".into())));
+    ///
+    /// let tool = parse_slice::<Tool>(&["-o"]).unwrap();
+    /// assert_eq!((tool.sources, tool.output), (vec![ValueSource::Following], None));
+    ///
+    /// let tool = parse_slice::<Tool>(&["-ofile"]).unwrap();
+    /// assert_eq!(tool.sources, [ValueSource::Cluster]);
+    /// assert_eq!(parse_slice::<Tool>(&["-o", "file"]).unwrap().sources, [ValueSource::Following]);
+    /// ```
+    fn source(&self) -> ValueSource {
+        ValueSource::None
+    }
+
+    /// Take every value up to the next flag or the end of the arguments, for variadic
+    /// arguments like `cp SRC... DEST`. After a `--` separator, flags do not stop collection.
+    fn take_all(&mut self) -> Vec<String> {
+        let mut values = Vec::new();
+        while self.peek().is_some() {
+            match self.take() {
+                Ok(value) => values.push(value),
+                Err(_) => break,
+            }
+        }
+        values
+    }
+
+    /// Every argument left, as-is, for flags that consume a run of arguments up to a
+    /// terminator of their own, like `find -exec cmd args \;`. Only the arguments taken from
+    /// the iterator are consumed; parsing carries on with the rest. A flag with a value
+    /// attached, as in `--exec=cmd`, has only that value.
+    ///
+    /// ```
+    /// # use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+    /// #[derive(Default)]
+    /// struct Find {
+    ///     exec: Vec<String>,
+    ///     paths: Vec<String>,
+    /// }
+    ///
+    /// impl ArgumentParser for Find {
+    ///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+    ///         match long {
+    ///             "exec" => {
+    ///                 self.exec = next.rest().take_while(|arg| arg != ";").collect();
+    ///                 Ok(())
+    ///             }
+    ///             _ => Err(ParseError::Unhandled),
+    ///         }
+    ///     }
+    ///
+    ///     fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+    ///         self.paths.push(arg.into());
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// let find = parse_slice::<Find>(&["--exec", "rm", "-f", "{}", ";", "src"]).unwrap();
+    /// assert_eq!(find.exec, ["rm", "-f", "{}"]);
+    /// assert_eq!(find.paths, ["src"]);
+    /// ```
+    fn rest(&mut self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(std::iter::empty())
+    }
+}
+
+/// Where a value given to a handler comes from. See [`Next::source`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueSource {
+    /// After an `=`, as in `--flag=value` or `-f=value`, where it may be empty. Values from
+    /// [`env_fallbacks`](crate::ArgumentParser::env_fallbacks) are attached too.
+    Attached,
+    /// The rest of a short flag cluster, as in `-fvalue`.
+    Cluster,
+    /// The following argument, if there is one.
+    Following,
+    /// There is no value to take, as for each repeat in `-vv`.
+    None,
+}
+
+/// Take the next value and parse it with [`FromStr`], for flags like `--port 8080`.
+/// A value that does not parse fails with [`ParseError::InvalidValue`].
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use krh_args_parser::{parse_slice, parse_value, ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// enum Color {
+///     #[default]
+///     Auto,
+///     Never,
+/// }
+///
+/// impl FromStr for Color {
+///     type Err = &'static str;
+///
+///     fn from_str(s: &str) -> Result<Self, Self::Err> {
+///         match s {
+///             "auto" => Ok(Self::Auto),
+///             "never" => Ok(Self::Never),
+///             _ => Err("expected auto or never"),
+///         }
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// struct Server {
+///     port: u16,
+///     color: Color,
+/// }
+///
+/// impl ArgumentParser for Server {
+///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+///         match long {
+///             "port" => self.port = parse_value(next)?,
+///             "color" => self.color = parse_value(next)?,
+///             _ => return Err(ParseError::Unhandled),
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let server = parse_slice::<Server>(&["--port", "8080", "--color=never"]).unwrap();
+/// assert_eq!(server.port, 8080);
+/// assert!(matches!(server.color, Color::Never));
+///
+/// let error = parse_slice::<Server>(&["--port", "99999"]).err().unwrap();
+/// assert_eq!(
+///     error.to_string(),
+///     "at argument 1: Invalid value '99999' for '--port': number too large to fit in target type"
+/// );
+/// let error = parse_slice::<Server>(&["--color=red"]).err().unwrap();
+/// assert_eq!(
+///     error.to_string(),
+///     "at argument 1: Invalid value 'red' for '--color': expected auto or never"
+/// );
+/// ```
+pub fn parse_value<T: FromStr>(next: &mut dyn Next) -> Result<T, ParseError>
+where
+    T::Err: Display,
+{
+    let value = next.take()?;
+    value.parse().map_err(|e: T::Err| ParseError::InvalidValue {
+        flag: next.flag(),
+        reason: e.to_string().into(),
+        value,
+    })
+}
+
+/// A value attached to its flag, as in `--flag=value` or `-ovalue`.
+pub(crate) struct Attached<'a> {
+    pub(crate) value: &'a str,
+    pub(crate) of: ValueOf<'a>,
+    pub(crate) taken: bool,
+    pub(crate) source: ValueSource,
+}
+
+impl<'a> Attached<'a> {
+    pub(crate) fn new(value: &'a str, of: ValueOf<'a>) -> Self {
+        Self {
+            value,
+            of,
+            taken: false,
+            source: ValueSource::Attached,
+        }
+    }
+
+    /// A value that is the rest of a short flag cluster.
+    pub(crate) fn cluster(value: &'a str, of: ValueOf<'a>) -> Self {
+        Self {
+            source: ValueSource::Cluster,
+            ..Self::new(value, of)
+        }
+    }
+}
+
+impl Next for Attached<'_> {
+    fn peek(&mut self) -> Option<Cow<'_, str>> {
+        (!self.taken).then_some(self.value.into())
+    }
+
+    fn take(&mut self) -> Result<String, ParseError> {
+        self.taken = true;
+        Ok(self.value.into())
+    }
+
+    fn take_n(&mut self, n: usize) -> Result<Vec<String>, ParseError> {
+        // Only one value can be attached.
+        match n {
+            0 => Ok(Vec::new()),
+            1 => Ok(vec![self.take()?]),
+            _ => Err(ParseError::TooFewValues {
+                flag: self.of.flag(),
+                expected: n,
+                got: 1,
+            }),
+        }
+    }
+
+    fn flag(&self) -> Option<String> {
+        self.of.flag()
+    }
+
+    fn source(&self) -> ValueSource {
+        self.source
+    }
+
+    fn rest(&mut self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(std::iter::from_fn(|| {
+            (!self.taken).then(|| {
+                self.taken = true;
+                self.value.to_owned()
+            })
+        }))
+    }
+}
+
+/// A source of values that can say which values a handler could take from it, so they can
+/// be checked before the handler runs.
+pub(crate) trait Lookahead: Next {
+    /// Call `each` with the values that could be taken, in the order they would be. Values
+    /// taken past these are possible, as with [`Next::rest`].
+    fn ahead(&mut self, each: &mut dyn FnMut(&str));
+}
+
+/// What [`ArgumentParser::validate_value`](crate::ArgumentParser::validate_value) made of
+/// the values of [`Lookahead::ahead`]. Every source hands its values out in order, so the
+/// values are matched up by position.
+#[derive(Default)]
+pub(crate) struct Verdicts {
+    /// The number of values checked ahead.
+    pub(crate) ahead: usize,
+    /// The position and reason of each value rejected, in order.
+    pub(crate) rejected: Vec<(usize, Cow<'static, str>)>,
+    /// The number of values handed over so far.
+    taken: usize,
+    /// Values taken past those checked ahead, to check once the handler returns.
+    pub(crate) unchecked: Vec<String>,
+}
+
+impl Verdicts {
+    /// Hand `value` over if it was not rejected.
+    fn check(
+        &mut self,
+        value: String,
+        flag: impl FnOnce() -> Option<String>,
+    ) -> Result<String, ParseError> {
+        let position = self.taken;
+        self.taken += 1;
+        if position >= self.ahead {
+            self.unchecked.push(value.clone());
+            return Ok(value);
+        }
+        match self
+            .rejected
+            .iter()
+            .find(|(rejected, _)| *rejected == position)
+        {
+            Some((_, reason)) => Err(ParseError::InvalidValue {
+                flag: flag(),
+                value,
+                reason: reason.clone(),
+            }),
+            None => Ok(value),
+        }
+    }
+}
+
+/// Another source of values, checking each value taken from it before handing it over.
+pub(crate) struct Checked<'a> {
+    pub(crate) next: &'a mut dyn Next,
+    pub(crate) verdicts: Verdicts,
+    /// The first value rejected by [`Next::take_all`] or [`Next::rest`], which cannot fail.
+    pub(crate) error: Option<ParseError>,
+}
+
+impl Checked<'_> {
+    fn check(&mut self, value: String) -> Result<String, ParseError> {
+        let next = &self.next;
+        self.verdicts.check(value, || next.flag())
+    }
+}
+
+impl Next for Checked<'_> {
+    fn peek(&mut self) -> Option<Cow<'_, str>> {
+        self.next.peek()
+    }
+
+    fn take(&mut self) -> Result<String, ParseError> {
+        let value = self.next.take()?;
+        self.check(value)
+    }
+
+    fn take_allow_dash(&mut self) -> Result<String, ParseError> {
+        let value = self.next.take_allow_dash()?;
+        self.check(value)
+    }
+
+    fn take_n(&mut self, n: usize) -> Result<Vec<String>, ParseError> {
+        let values = self.next.take_n(n)?;
+        // Check every value, so later takes line up with their verdicts.
+        let checked: Vec<_> = values.into_iter().map(|value| self.check(value)).collect();
+        checked.into_iter().collect()
+    }
+
+    fn flag(&self) -> Option<String> {
+        self.next.flag()
+    }
+
+    fn source(&self) -> ValueSource {
+        self.next.source()
+    }
+
+    /// Gives no values if any is rejected, and the handler fails once it returns.
+    fn take_all(&mut self) -> Vec<String> {
+        let values = self.next.take_all();
+        let checked: Vec<_> = values.into_iter().map(|value| self.check(value)).collect();
+        match checked.into_iter().collect() {
+            Ok(values) => values,
+            Err(e) => {
+                self.error = Some(e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Ends before a rejected value, and the handler fails once it returns.
+    fn rest(&mut self) -> Box<dyn Iterator<Item = String> + '_> {
+        let of = self.next.flag();
+        let Self {
+            next,
+            verdicts,
+            error,
+        } = self;
+        let mut rest = next.rest();
+        Box::new(std::iter::from_fn(move || {
+            if error.is_some() {
+                return None;
+            }
+            let value = rest.next()?;
+            match verdicts.check(value, || of.clone()) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    *error = Some(e);
+                    None
+                }
+            }
+        }))
+    }
+}
+
+impl Lookahead for Attached<'_> {
+    fn ahead(&mut self, each: &mut dyn FnMut(&str)) {
+        if !self.taken {
+            each(self.value);
+        }
+    }
+}
+
+impl Lookahead for NoValue {
+    fn ahead(&mut self, _each: &mut dyn FnMut(&str)) {}
+}
+
+impl Lookahead for Following<'_, '_> {
+    /// The following argument, which [`Next::take_allow_dash`] may take even if it looks
+    /// like a flag, then each argument [`Next::take_all`] would take.
+    fn ahead(&mut self, each: &mut dyn FnMut(&str)) {
+        let mut n = 0;
+        while let Some(arg) = self.args.peek_nth(n) {
+            let arg = arg.to_string_lossy();
+            if n > 0 && !self.options_ended && !(self.is_value)(&arg) {
+                break;
+            }
+            each(&arg);
+            n += 1;
+        }
+    }
+}
+
+/// No value is available; taking one fails with the given error.
+pub(crate) struct NoValue(pub(crate) ParseError);
+
+impl Next for NoValue {
+    fn peek(&mut self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    fn take(&mut self) -> Result<String, ParseError> {
+        Err(self.0.clone())
+    }
+
+    fn take_n(&mut self, n: usize) -> Result<Vec<String>, ParseError> {
+        match n {
+            0 => Ok(Vec::new()),
+            _ => Err(self.0.clone()),
+        }
+    }
+}
+
+/// What a value is for.
+pub(crate) enum ValueOf<'a> {
+    Long { prefix: &'a str, long: &'a str },
+    Short { prefix: char, short: char },
+    Positional,
+}
+
+impl ValueOf<'_> {
+    /// The flag as given, like `--output` or `-o`.
+    fn flag(&self) -> Option<String> {
+        match self {
+            Self::Long { prefix, long } => Some(format!("{prefix}{long}")),
+            Self::Short { prefix, short } => Some(format!("{prefix}{short}")),
+            Self::Positional => None,
+        }
+    }
+}
+
+/// The value is the following argument.
+pub(crate) struct Following<'a, 'args> {
+    pub(crate) args: &'a mut Args<'args>,
+    pub(crate) of: ValueOf<'a>,
+    /// Whether an argument may be taken as a flag's value rather than being a flag itself.
+    pub(crate) is_value: &'a dyn Fn(&str) -> bool,
+    pub(crate) taken_count: &'a Cell<usize>,
+    /// Whether a `--` separator has been seen, so flags no longer end [`Next::take_all`].
+    pub(crate) options_ended: bool,
+    /// Whether [`Next::take`] acts like [`Next::take_allow_dash`].
+    pub(crate) allow_dash: bool,
+}
+
+impl Following<'_, '_> {
+    /// Take the following argument. If `strict`, one that looks like a flag is an error.
+    fn take_checked(&mut self, strict: bool) -> Result<String, ParseError> {
+        let Some(next) = self.args.peek() else {
+            return Err(match self.of {
+                ValueOf::Positional => ParseError::MissingArgument,
+                _ => ParseError::MissingValue {
+                    flag: self.of.flag().unwrap_or_default(),
+                },
+            });
+        };
+
+        let next = next.to_string_lossy();
+        let is_separator = next == "--" && !self.options_ended;
+        if is_separator && !matches!(self.of, ValueOf::Positional)
+            || strict && !(self.is_value)(&next)
+        {
+            return Err(ParseError::ExpectedValue {
+                got: next.into_owned(),
+            });
+        }
+
+        self.taken_count.set(self.taken_count.get() + 1);
+        Ok(lossy(self.args.next().unwrap()))
+    }
+}
+
+impl Next for Following<'_, '_> {
+    fn peek(&mut self) -> Option<Cow<'_, str>> {
+        self.args.peek().map(|arg| arg.to_string_lossy())
+    }
+
+    fn take(&mut self) -> Result<String, ParseError> {
+        let strict = !matches!(self.of, ValueOf::Positional) && !self.allow_dash;
+        self.take_checked(strict)
+    }
+
+    fn take_allow_dash(&mut self) -> Result<String, ParseError> {
+        self.take_checked(false)
+    }
+
+    fn flag(&self) -> Option<String> {
+        self.of.flag()
+    }
+
+    fn source(&self) -> ValueSource {
+        ValueSource::Following
+    }
+
+    fn take_all(&mut self) -> Vec<String> {
+        let mut values = Vec::new();
+        while let Some(next) = self.args.peek() {
+            if !self.options_ended && !(self.is_value)(&next.to_string_lossy()) {
+                break;
+            }
+            self.taken_count.set(self.taken_count.get() + 1);
+            values.push(lossy(self.args.next().unwrap()));
+        }
+        values
+    }
+
+    fn rest(&mut self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(std::iter::from_fn(|| {
+            let arg = self.args.next()?;
+            self.taken_count.set(self.taken_count.get() + 1);
+            Some(lossy(arg))
+        }))
+    }
+}
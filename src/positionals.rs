@@ -0,0 +1,66 @@
+use crate::{ArgumentParser, Next, ParseError, ValueSource};
+
+/// A parser that collects every positional argument, for tools that take nothing else.
+/// Flags are unhandled, unless `IGNORE_FLAGS` is set to skip them. A flag's value is not
+/// skipped with it, so it is collected as a positional, and a flag with a value attached,
+/// as in `--force=x`, is collected whole.
+///
+/// ```
+/// # use krh_args_parser::{parse_slice, Positionals};
+/// let args = parse_slice::<Positionals>(&["a", "b", "c"]).unwrap();
+/// assert_eq!(args, Positionals(vec!["a".into(), "b".into(), "c".into()]));
+/// assert_eq!(
+///     parse_slice::<Positionals>(&["a", "-x"]).err().unwrap().to_string(),
+///     "at argument 2: Unrecognized argument"
+/// );
+///
+/// let Positionals(args) = parse_slice::<Positionals<true>>(&["a", "--force", "b"]).unwrap();
+/// assert_eq!(args, ["a", "b"]);
+/// let Positionals(args) = parse_slice::<Positionals<true>>(&["a", "--force=b"]).unwrap();
+/// assert_eq!(args, ["a", "--force=b"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Positionals<const IGNORE_FLAGS: bool = false>(pub Vec<String>);
+
+impl<const IGNORE_FLAGS: bool> ArgumentParser for Positionals<IGNORE_FLAGS> {
+    fn allow_no_args(&self) -> bool {
+        true
+    }
+
+    fn long(&mut self, _long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        // Declined either way, so `unknown_long` sees the whole argument.
+        Err(ParseError::Unhandled)
+    }
+
+    fn unknown_long(&mut self, flag: &str) -> Result<(), ParseError> {
+        if !IGNORE_FLAGS {
+            return Err(ParseError::Unhandled);
+        }
+        if flag.contains(self.value_separator()) {
+            self.0.push(flag.into());
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        if !IGNORE_FLAGS {
+            return Err(ParseError::Unhandled);
+        }
+        if next.source() == ValueSource::Attached {
+            let value = next.take()?;
+            self.0
+                .push(format!("-{short}{}{value}", self.value_separator()));
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.0.push(arg.into());
+        Ok(false)
+    }
+}
@@ -0,0 +1,227 @@
+/// A declarative description of a command, for generating completions and documentation.
+/// See [`ArgumentParser::describe`](crate::ArgumentParser::describe).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandSpec {
+    /// The command name, as typed by the user.
+    pub name: &'static str,
+    /// A one-line description of the command.
+    pub about: &'static str,
+    pub flags: Vec<FlagSpec>,
+    pub subcommands: Vec<CommandSpec>,
+}
+
+impl CommandSpec {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+
+    pub fn about(mut self, about: &'static str) -> Self {
+        self.about = about;
+        self
+    }
+
+    pub fn flag(mut self, flag: FlagSpec) -> Self {
+        self.flags.push(flag);
+        self
+    }
+
+    pub fn subcommand(mut self, subcommand: CommandSpec) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    /// An `Options:` section for [`help`](crate::ArgumentParser::help), with one aligned line
+    /// per flag giving its help text and any default, like `(default: 4)`.
+    /// Empty if there are no flags.
+    pub fn options_help(&self) -> String {
+        let entries = self
+            .flags
+            .iter()
+            .map(|flag| (flag.usage(), flag.help_text()))
+            .collect();
+        section("Options:", entries)
+    }
+}
+
+/// The widest first column in a help section. Longer entries put their text on the next
+/// line instead of pushing every other line over.
+const MAX_COLUMN: usize = 24;
+
+/// A help section with a `title` line and one line per entry, with the text aligned.
+/// Widths are counted in characters, as padding with `format!` is.
+fn section(title: &str, entries: Vec<(String, String)>) -> String {
+    let width = entries.iter().map(|(name, _)| name.chars().count()).max();
+    let Some(width) = width.map(|width| width.min(MAX_COLUMN)) else {
+        return String::new();
+    };
+
+    let mut help = String::from(title);
+    for (name, text) in entries {
+        let line = if name.chars().count() > width && !text.is_empty() {
+            format!("  {name}\n  {:width$}  {text}", "")
+        } else {
+            format!("  {name:width$}  {text}")
+        };
+        help.push('\n');
+        help.push_str(line.trim_end());
+    }
+    help
+}
+
+/// A standard help message for `spec`, with its description, a usage line, and aligned
+/// `Options:` and `Commands:` sections, leaving out any that would be empty. This is the
+/// default [`help`](crate::ArgumentParser::help), made from
+/// [`describe`](crate::ArgumentParser::describe).
+///
+/// ```
+/// # use krh_args_parser::{default_help, CommandSpec, FlagSpec};
+/// let spec = CommandSpec::new("tool")
+///     .about("Does things")
+///     .flag(FlagSpec::long("verbose").with_short('v').help("Print more"))
+///     .flag(FlagSpec::long("jobs").takes_value().help("Threads to use").with_default("4"))
+///     .flag(FlagSpec::long("a-very-long-flag-name").help("Wraps"))
+///     .subcommand(CommandSpec::new("build").about("Build the project"));
+///
+/// assert_eq!(
+///     default_help(&spec),
+///     "\
+/// Does things
+///
+/// Usage: tool [OPTIONS] [COMMAND]
+///
+/// Options:
+///   -v, --verbose             Print more
+///       --jobs <VALUE>        Threads to use (default: 4)
+///       --a-very-long-flag-name
+///                             Wraps
+///
+/// Commands:
+///   build  Build the project"
+/// );
+/// ```
+pub fn default_help(spec: &CommandSpec) -> String {
+    let usage = [
+        spec.name,
+        if spec.flags.is_empty() {
+            ""
+        } else {
+            "[OPTIONS]"
+        },
+        if spec.subcommands.is_empty() {
+            ""
+        } else {
+            "[COMMAND]"
+        },
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ");
+    let usage = if usage.is_empty() {
+        usage
+    } else {
+        format!("Usage: {usage}")
+    };
+
+    let commands = spec
+        .subcommands
+        .iter()
+        .map(|command| (command.name.to_owned(), command.about.to_owned()))
+        .collect();
+    [
+        spec.about.to_owned(),
+        usage,
+        spec.options_help(),
+        section("Commands:", commands),
+    ]
+    .into_iter()
+    .filter(|block| !block.is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+/// A flag in a [`CommandSpec`]. At least one of `long` and `short` should be set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagSpec {
+    /// The long name, without the leading `--`.
+    pub long: Option<&'static str>,
+    pub short: Option<char>,
+    /// Whether the flag takes a value.
+    pub takes_value: bool,
+    pub help: &'static str,
+    /// The value used when the flag is not given, shown in help.
+    pub default: Option<&'static str>,
+}
+
+impl FlagSpec {
+    /// A long flag `--long`.
+    pub fn long(long: &'static str) -> Self {
+        Self {
+            long: Some(long),
+            ..Self::default()
+        }
+    }
+
+    /// A short flag `-short`, with no long name.
+    pub fn short(short: char) -> Self {
+        Self {
+            short: Some(short),
+            ..Self::default()
+        }
+    }
+
+    /// Also accept this flag as `-short`.
+    pub fn with_short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    /// Mark the flag as taking a value.
+    pub fn takes_value(mut self) -> Self {
+        self.takes_value = true;
+        self
+    }
+
+    pub fn help(mut self, help: &'static str) -> Self {
+        self.help = help;
+        self
+    }
+
+    pub fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// The flag's names and value, like `-o, --output <VALUE>`.
+    pub(crate) fn usage(&self) -> String {
+        let mut usage = match (self.short, self.long) {
+            (Some(short), Some(long)) => format!("-{short}, --{long}"),
+            (Some(short), None) => format!("-{short}"),
+            (None, Some(long)) => format!("    --{long}"),
+            (None, None) => String::new(),
+        };
+        if self.takes_value {
+            usage.push_str(" <VALUE>");
+        }
+        usage
+    }
+
+    /// The help text with the default appended.
+    pub(crate) fn help_text(&self) -> String {
+        match self.default {
+            Some(default) if self.help.is_empty() => format!("(default: {default})"),
+            Some(default) => format!("{} (default: {default})", self.help),
+            None => self.help.to_owned(),
+        }
+    }
+
+    /// Every spelling of the flag, like `-v` and `--verbose`.
+    pub(crate) fn names(&self) -> impl Iterator<Item = String> {
+        let short = self.short.map(|short| format!("-{short}"));
+        let long = self.long.map(|long| format!("--{long}"));
+        short.into_iter().chain(long)
+    }
+}
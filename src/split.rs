@@ -0,0 +1,72 @@
+use crate::ParseError;
+
+/// Split a command line into arguments like a POSIX shell would, without any expansion.
+///
+/// Arguments are separated by unquoted whitespace. Inside single quotes everything is literal;
+/// inside double quotes a backslash only escapes `"`, `\`, `$`, `` ` `` and newlines.
+/// Elsewhere a backslash escapes any character, and a backslash-newline is removed.
+pub(crate) fn split(line: &str) -> Result<Vec<String>, ParseError> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    // Whether an argument has started, so that `''` still produces an empty argument.
+    let mut in_arg = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(ParseError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('\n') => {}
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(ParseError::UnterminatedQuote('"')),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(ParseError::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => {
+                    in_arg = true;
+                    current.push(c);
+                }
+                None => return Err(ParseError::TrailingEscape),
+            },
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
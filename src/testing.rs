@@ -0,0 +1,69 @@
+//! Helpers for testing an [`ArgumentParser`], which panic with the arguments and the error
+//! when parsing does not go as expected.
+//!
+//! Both parse with [`parse_slice`], so nothing is read from the process.
+
+use crate::{parse_slice, ArgumentParser};
+
+/// Parse `args` and pass the parser to `check`, panicking if parsing fails.
+///
+/// ```
+/// # use krh_args_parser::testing::assert_parses;
+/// # use krh_args_parser::{ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// struct Tool {
+///     output: Option<String>,
+/// }
+///
+/// impl ArgumentParser for Tool {
+///     fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+///         match long {
+///             "output" => self.output = Some(next.take()?),
+///             _ => return Err(ParseError::Unhandled),
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// assert_parses::<Tool>(&["--output", "x"], |tool| {
+///     assert_eq!(tool.output.as_deref(), Some("x"));
+/// });
+/// ```
+#[track_caller]
+pub fn assert_parses<P: ArgumentParser>(args: &[&str], check: impl FnOnce(P)) {
+    match parse_slice::<P>(args) {
+        Ok(parser) => check(parser),
+        Err(e) => panic!("parsing {args:?} failed: {e}"),
+    }
+}
+
+/// Parse `args`, panicking unless parsing fails with an error whose message contains
+/// `expected`.
+///
+/// ```
+/// # use krh_args_parser::testing::assert_error;
+/// # use krh_args_parser::{unknown_flag, ArgumentParser, Next, ParseError};
+/// #[derive(Default)]
+/// struct Tool;
+///
+/// impl ArgumentParser for Tool {
+///     fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+///         Err(unknown_flag(&format!("--{long}")).into())
+///     }
+/// }
+///
+/// assert_error::<Tool>(&["--bad"], "Unknown flag '--bad'");
+/// ```
+#[track_caller]
+pub fn assert_error<P: ArgumentParser>(args: &[&str], expected: &str) {
+    match parse_slice::<P>(args) {
+        Ok(_) => panic!("parsing {args:?} succeeded, expected an error containing {expected:?}"),
+        Err(e) => {
+            let message = e.to_string();
+            assert!(
+                message.contains(expected),
+                "parsing {args:?} failed with {message:?}, expected an error containing {expected:?}"
+            );
+        }
+    }
+}
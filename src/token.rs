@@ -0,0 +1,260 @@
+use crate::{CommandSpec, FlagSpec};
+
+/// The shape of a single argument, as found by [`classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// A standalone `--`, after which every argument is positional.
+    Separator,
+    /// A long flag `--name`, with the value from `--name=value` if given.
+    /// The name is empty for something like `--=value`.
+    Long {
+        name: &'a str,
+        value: Option<&'a str>,
+    },
+    /// A cluster of short flags like `-abc`, without the `-`.
+    /// It may hold `=` values, as in `-o=file`; see
+    /// [`ArgumentParser::short`](crate::ArgumentParser::short).
+    Short(&'a str),
+    /// Anything else, including a lone `-`.
+    Positional(&'a str),
+}
+
+/// Find the shape of `arg` on its own, the way parsing does by default.
+///
+/// Whether an argument is taken as a flag's value, and options like
+/// [`allow_negative_numbers`](crate::ArgumentParser::allow_negative_numbers), depend on the
+/// rest of the command line and the parser, so they are left to the caller.
+///
+/// ```
+/// # use krh_args_parser::{classify, Token};
+/// assert_eq!(classify("--"), Token::Separator);
+/// assert_eq!(classify("--name"), Token::Long { name: "name", value: None });
+/// assert_eq!(classify("--name=a=b"), Token::Long { name: "name", value: Some("a=b") });
+/// assert_eq!(classify("--name="), Token::Long { name: "name", value: Some("") });
+/// assert_eq!(classify("--dry-run=a-b"), Token::Long { name: "dry-run", value: Some("a-b") });
+/// assert_eq!(classify("--no-dry-run"), Token::Long { name: "no-dry-run", value: None });
+/// assert_eq!(classify("--=x"), Token::Long { name: "", value: Some("x") });
+/// assert_eq!(classify("-v"), Token::Short("v"));
+/// assert_eq!(classify("-vo=file"), Token::Short("vo=file"));
+/// assert_eq!(classify("-5"), Token::Short("5"));
+/// assert_eq!(classify("-"), Token::Positional("-"));
+/// assert_eq!(classify("file"), Token::Positional("file"));
+/// assert_eq!(classify(""), Token::Positional(""));
+/// ```
+pub fn classify(arg: &str) -> Token<'_> {
+    classify_with(arg, '=')
+}
+
+/// Like [`classify`], but with `separator` between a long flag and its value.
+pub(crate) fn classify_with(arg: &str, separator: char) -> Token<'_> {
+    if arg == "--" {
+        Token::Separator
+    } else if let Some(s) = arg.strip_prefix("--") {
+        let (name, value) = split_value(s, separator);
+        Token::Long { name, value }
+    } else if let Some(s) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+        Token::Short(s)
+    } else {
+        Token::Positional(arg)
+    }
+}
+
+/// Split a flag's name from any value after the first `separator`.
+pub(crate) fn split_value(s: &str, separator: char) -> (&str, Option<&str>) {
+    match s.split_once(separator) {
+        Some((name, value)) => (name, Some(value)),
+        None => (s, None),
+    }
+}
+
+/// Where a cursor is on a command line, as found by [`token_at`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenContext<'a> {
+    pub kind: TokenKind,
+    /// The text of the token so far, like `--out` or the `fi` of `--output=fi`.
+    pub partial: &'a str,
+    /// The command the cursor is in, which is a subcommand once one has been given.
+    pub command: &'a CommandSpec,
+}
+
+/// What a cursor is on, for [`TokenContext`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A flag name, or a cluster of short flags.
+    Flag,
+    /// The value of `flag`, spelled as given like `--output` or `-o`.
+    Value { flag: String },
+    /// A positional argument, with the number of positionals before it in its command.
+    Positional { index: usize },
+}
+
+impl TokenContext<'_> {
+    /// What the token could be completed to: the matching flags for a flag name, or the
+    /// matching subcommands for the first positional. Values cannot be completed from a
+    /// [`CommandSpec`], so have no completions.
+    pub fn completions(&self) -> Vec<String> {
+        let candidates: Vec<String> = match self.kind {
+            TokenKind::Flag => self
+                .command
+                .flags
+                .iter()
+                .flat_map(FlagSpec::names)
+                .collect(),
+            TokenKind::Positional { index: 0 } => self
+                .command
+                .subcommands
+                .iter()
+                .map(|command| command.name.to_owned())
+                .collect(),
+            _ => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(self.partial))
+            .collect()
+    }
+}
+
+/// Find what the argument at `cursor_arg` of a partly typed command line is, for editor
+/// and shell integrations. `args` are without the program name, and `cursor_arg` may be
+/// `args.len()` for a new argument that has not been started. Whether a flag takes a value,
+/// and which subcommands there are, comes from `spec`.
+///
+/// ```
+/// # use krh_args_parser::{token_at, CommandSpec, FlagSpec, TokenKind};
+/// let spec = CommandSpec::new("tool")
+///     .flag(FlagSpec::long("output").with_short('o').takes_value())
+///     .flag(FlagSpec::long("verbose").with_short('v'))
+///     .subcommand(CommandSpec::new("build"));
+/// let args = ["--out", "--output", "fi", "-vofi", "bu"].map(String::from);
+///
+/// let context = token_at(&spec, &args, 0);
+/// assert_eq!((&context.kind, context.partial), (&TokenKind::Flag, "--out"));
+/// assert_eq!(context.completions(), ["--output"]);
+///
+/// let context = token_at(&spec, &args, 2);
+/// assert_eq!(context.kind, TokenKind::Value { flag: "--output".into() });
+/// assert_eq!(context.partial, "fi");
+///
+/// let context = token_at(&spec, &args, 3);
+/// assert_eq!(context.kind, TokenKind::Value { flag: "-o".into() });
+/// assert_eq!(context.partial, "fi");
+///
+/// let context = token_at(&spec, &args, 4);
+/// assert_eq!(context.kind, TokenKind::Positional { index: 0 });
+/// assert_eq!(context.completions(), ["build"]);
+///
+/// let args = ["build", "--verbose=", "x"].map(String::from);
+/// let context = token_at(&spec, &args, 1);
+/// assert_eq!(context.command.name, "build");
+/// assert_eq!(context.kind, TokenKind::Value { flag: "--verbose".into() });
+/// assert_eq!(token_at(&spec, &args, 3).kind, TokenKind::Positional { index: 1 });
+/// ```
+pub fn token_at<'a>(
+    spec: &'a CommandSpec,
+    args: &'a [String],
+    cursor_arg: usize,
+) -> TokenContext<'a> {
+    let mut command = spec;
+    let mut options_ended = false;
+    let mut positionals = 0;
+    // A flag before the cursor that is still waiting for its value.
+    let mut pending: Option<String> = None;
+
+    for arg in args.iter().take(cursor_arg) {
+        if pending.take().is_some() {
+            continue;
+        }
+        let token = if options_ended {
+            Token::Positional(arg)
+        } else {
+            classify(arg)
+        };
+        match token {
+            Token::Separator => options_ended = true,
+            Token::Long { name, value: None } => {
+                let takes_value = find_flag(command, |flag| flag.long == Some(name))
+                    .is_some_and(|flag| flag.takes_value);
+                if takes_value {
+                    pending = Some(format!("--{name}"));
+                }
+            }
+            Token::Long { .. } => {}
+            Token::Short(cluster) => {
+                if let (Some(short), None) = short_value(command, cluster) {
+                    pending = Some(format!("-{short}"));
+                }
+            }
+            Token::Positional(arg) => {
+                let subcommand = command
+                    .subcommands
+                    .iter()
+                    .find(|command| command.name == arg)
+                    .filter(|_| positionals == 0 && !options_ended);
+                if let Some(subcommand) = subcommand {
+                    command = subcommand;
+                    positionals = 0;
+                } else {
+                    positionals += 1;
+                }
+            }
+        }
+    }
+
+    let partial = args.get(cursor_arg).map_or("", String::as_str);
+    let context = |kind, partial| TokenContext {
+        kind,
+        partial,
+        command,
+    };
+    let positional = TokenKind::Positional { index: positionals };
+    if let Some(flag) = pending {
+        return context(TokenKind::Value { flag }, partial);
+    }
+    if options_ended {
+        return context(positional, partial);
+    }
+
+    match classify(partial) {
+        Token::Separator => context(TokenKind::Flag, partial),
+        Token::Long {
+            name,
+            value: Some(value),
+        } => context(
+            TokenKind::Value {
+                flag: format!("--{name}"),
+            },
+            value,
+        ),
+        Token::Long { .. } => context(TokenKind::Flag, partial),
+        Token::Short(cluster) => match short_value(command, cluster) {
+            (Some(short), Some(value)) => context(
+                TokenKind::Value {
+                    flag: format!("-{short}"),
+                },
+                value,
+            ),
+            _ => context(TokenKind::Flag, partial),
+        },
+        Token::Positional(_) => context(positional, partial),
+    }
+}
+
+/// The first short flag in `cluster` that takes a value, with the value attached to it if
+/// any, as in `-ofile` or `-o=file`.
+fn short_value<'a>(command: &CommandSpec, cluster: &'a str) -> (Option<char>, Option<&'a str>) {
+    for (i, short) in cluster.char_indices() {
+        let takes_value = find_flag(command, |flag| flag.short == Some(short))
+            .is_some_and(|flag| flag.takes_value);
+        if takes_value {
+            let rest = &cluster[i + short.len_utf8()..];
+            let rest = rest.strip_prefix('=').unwrap_or(rest);
+            return (Some(short), Some(rest).filter(|rest| !rest.is_empty()));
+        }
+    }
+    (None, None)
+}
+
+fn find_flag(command: &CommandSpec, matches: impl Fn(&FlagSpec) -> bool) -> Option<&FlagSpec> {
+    command.flags.iter().find(|flag| matches(flag))
+}
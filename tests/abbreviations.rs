@@ -0,0 +1,38 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    flags: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn known_longs(&self) -> &[&'static str] {
+        &["verbose", "version-info", "output"]
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        self.flags.push(long.into());
+        Ok(())
+    }
+}
+
+#[test]
+fn a_unique_prefix_is_expanded() {
+    assert_parses::<Tool>(&["--verb", "--version-", "--o"], |tool| {
+        assert_eq!(tool.flags, ["verbose", "version-info", "output"])
+    });
+}
+
+#[test]
+fn an_exact_name_is_used_as_is() {
+    assert_parses::<Tool>(&["--output"], |tool| assert_eq!(tool.flags, ["output"]));
+}
+
+#[test]
+fn a_shared_prefix_is_ambiguous() {
+    assert_error::<Tool>(
+        &["--output", "--ver"],
+        "at argument 2: Flag 'ver' is ambiguous, could be any of: verbose, version-info",
+    );
+}
@@ -0,0 +1,50 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, ParseError};
+
+/// `tool [build|test] [ARGS...]`, where `test` wants `--release` to have been given to it.
+#[derive(Default)]
+struct Tool {
+    command: String,
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn subcommands(&self) -> &[&'static str] {
+        &["build", "test"]
+    }
+
+    fn subcommand(
+        &mut self,
+        command: &str,
+        command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<(), ParseError> {
+        self.command = command.into();
+        self.args.extend(command_args);
+        Ok(())
+    }
+
+    fn after_subcommand(&mut self, command: &str) -> Result<(), ParseError> {
+        if command == "test" && !self.args.iter().any(|arg| arg == "--release") {
+            return Err(ParseError::MissingFlag {
+                flag: "--release".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn runs_after_the_subcommand() {
+    assert_parses::<Tool>(&["build"], |tool| assert_eq!(tool.command, "build"));
+    assert_parses::<Tool>(&["test", "--release"], |tool| {
+        assert_eq!(tool.args, ["--release"])
+    });
+}
+
+#[test]
+fn errors_are_at_the_subcommand() {
+    assert_error::<Tool>(
+        &["test", "x"],
+        "at argument 1: Missing required flag '--release'",
+    );
+}
@@ -0,0 +1,51 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// `--trace` goes to `--debug` and on to `--verbose`, and `--a` and `--b` name each other.
+#[derive(Default)]
+struct Tool {
+    flags: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn aliases(&self) -> &[(&'static str, &'static str)] {
+        &[
+            ("--debug", "--verbose"),
+            ("--trace", "--debug"),
+            ("-t", "--trace"),
+            ("--a", "--b"),
+            ("--b", "--a"),
+        ]
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        self.flags.push(long.into());
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.flags.push(short.into());
+        Ok(())
+    }
+}
+
+#[test]
+fn chained_aliases_resolve_to_the_end() {
+    assert_parses::<Tool>(&["--trace", "--debug", "-t"], |tool| {
+        assert_eq!(tool.flags, ["verbose", "verbose", "verbose"])
+    });
+}
+
+#[test]
+fn a_cycle_is_an_error() {
+    assert_error::<Tool>(
+        &["--verbose", "--a"],
+        "at argument 2: Alias '--a' is part of a cycle",
+    );
+    assert_error::<Tool>(&["--b"], "at argument 1: Alias '--b' is part of a cycle");
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use krh_args_parser::{ParseError, ParserBuilder};
+
+fn parse(args: &[&str]) -> Result<HashMap<&'static str, String>, ParseError> {
+    ParserBuilder::new(HashMap::new())
+        .short('v', |state, _next| {
+            state.insert("verbose", "true".into());
+            Ok(())
+        })
+        .long("name", |state, next| {
+            state.insert("name", next.take()?);
+            Ok(())
+        })
+        .build()
+        .parse(
+            args.iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+                .into_iter(),
+        )
+}
+
+#[test]
+fn handlers_run() {
+    let state = parse(&["-v", "--name", "world"]).unwrap();
+    assert_eq!(state["verbose"], "true");
+    assert_eq!(state["name"], "world");
+}
+
+#[test]
+fn unknown_flags_are_unhandled() {
+    for (args, index) in [(&["--name", "x", "--other"][..], 3), (&["-vx"][..], 1)] {
+        match parse(args) {
+            Err(ParseError::At {
+                index: at, error, ..
+            }) => {
+                assert_eq!(at, index);
+                assert_eq!(*error, ParseError::Unhandled);
+            }
+            result => panic!("{args:?} gave {result:?}"),
+        }
+    }
+}
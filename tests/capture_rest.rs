@@ -0,0 +1,53 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// Runs a command given after `exec`, which must not be empty.
+#[derive(Default)]
+struct Tool {
+    verbose: bool,
+    command: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn capture_rest_after(&self) -> Option<&'static str> {
+        Some("exec")
+    }
+
+    fn rest(&mut self, args: &mut dyn Iterator<Item = String>) -> Result<(), ParseError> {
+        self.command.extend(args);
+        if self.command.first().is_some_and(String::is_empty) {
+            return Err(ParseError::InvalidValue {
+                flag: Some("exec".into()),
+                value: String::new(),
+                reason: "the command cannot be empty".into(),
+            });
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        _short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.verbose = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn rest_gets_everything_after() {
+    assert_parses::<Tool>(&["-v", "exec", "ls", "-l"], |tool| {
+        assert!(tool.verbose);
+        assert_eq!(tool.command, ["ls", "-l"]);
+    });
+}
+
+#[test]
+fn rest_errors_are_at_the_capturing_argument() {
+    assert_error::<Tool>(
+        &["-v", "exec", "", "x"],
+        "at argument 2: Invalid value '' for 'exec': the command cannot be empty",
+    );
+}
@@ -0,0 +1,43 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    verbose: bool,
+    flags: Vec<(String, String)>,
+}
+
+impl ArgumentParser for Tool {
+    fn case_insensitive_longs(&self) -> bool {
+        true
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            "flag" => self.flags.push((long.into(), next.take()?)),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn flag_names_are_lowercased() {
+    for arg in ["--verbose", "--Verbose", "--VERBOSE"] {
+        assert_parses::<Tool>(&[arg], |tool| assert!(tool.verbose));
+    }
+}
+
+#[test]
+fn values_keep_their_case() {
+    assert_parses::<Tool>(&["--FLAG=Value", "--Flag", "MiXeD"], |tool| {
+        assert_eq!(
+            tool.flags,
+            [
+                ("flag".into(), "Value".into()),
+                ("flag".into(), "MiXeD".into())
+            ]
+        );
+    });
+}
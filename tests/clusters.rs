@@ -0,0 +1,50 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// `-a` takes a value and `-v` is a switch.
+#[derive(Default)]
+struct Tool {
+    a: Vec<String>,
+    verbose: bool,
+}
+
+impl ArgumentParser for Tool {
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'a' => self.a.push(next.take()?),
+            'v' => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn a_value_flag_mid_cluster_takes_the_rest() {
+    assert_parses::<Tool>(&["-av"], |tool| {
+        assert_eq!(tool.a, ["v"]);
+        assert!(!tool.verbose);
+    });
+}
+
+#[test]
+fn a_value_flag_last_in_a_cluster_takes_the_next_argument() {
+    assert_parses::<Tool>(&["-va", "x"], |tool| {
+        assert_eq!(tool.a, ["x"]);
+        assert!(tool.verbose);
+    });
+    assert_error::<Tool>(
+        &["-va"],
+        "at argument 1: Flag '-a' expected a value but none was given",
+    );
+}
+
+#[test]
+fn a_repeated_value_flag_takes_the_rest_the_first_time() {
+    assert_parses::<Tool>(&["-aav"], |tool| assert_eq!(tool.a, ["av"]));
+}
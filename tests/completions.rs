@@ -0,0 +1,98 @@
+use krh_args_parser::{completions, CommandSpec, FlagSpec};
+
+/// A command with a switch, a flag taking a value, and a subcommand with its own flag.
+fn spec() -> CommandSpec {
+    CommandSpec::new("tool")
+        .about("Build things")
+        .flag(FlagSpec::long("verbose").with_short('v').help("Print more"))
+        .flag(
+            FlagSpec::long("output")
+                .takes_value()
+                .help("Where to write"),
+        )
+        .subcommand(
+            CommandSpec::new("build")
+                .about("Build a target")
+                .flag(FlagSpec::short('j').takes_value()),
+        )
+}
+
+#[test]
+fn bash() {
+    let expected = r#"_tool() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local command=tool i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "$command:${COMP_WORDS[i]}" in
+            tool:build) command=tool__build ;;
+        esac
+    done
+
+    case "$command" in
+        tool)
+            case "$prev" in
+                --output) COMPREPLY=($(compgen -f -- "$cur")); return ;;
+            esac
+            COMPREPLY=($(compgen -W "-v --verbose --output build" -- "$cur")) ;;
+        tool__build)
+            case "$prev" in
+                -j) COMPREPLY=($(compgen -f -- "$cur")); return ;;
+            esac
+            COMPREPLY=($(compgen -W "-j" -- "$cur")) ;;
+    esac
+}
+
+complete -F _tool tool
+"#;
+    assert_eq!(completions::bash(&spec()), expected);
+}
+
+#[test]
+fn zsh() {
+    let expected = r#"#compdef tool
+
+_tool() {
+    local context state state_descr line
+    typeset -A opt_args
+    _arguments -C \
+        '(-v --verbose)'{-v,--verbose}'[Print more]' \
+        '--output=[Where to write]:value:_files' \
+        '1: :->command' \
+        '*:: :->args'
+
+    case $state in
+        command)
+            local commands=(
+                'build:Build a target'
+            )
+            _describe command commands
+            ;;
+        args)
+            case $words[1] in
+                build) _tool__build ;;
+            esac
+            ;;
+    esac
+}
+
+_tool__build() {
+    _arguments \
+        '-j+:value:_files' \
+        '*: :_files'
+}
+
+_tool "$@"
+"#;
+    assert_eq!(completions::zsh(&spec()), expected);
+}
+
+#[test]
+fn fish() {
+    let expected = "\
+complete -c tool -f -n 'not __fish_seen_subcommand_from build' -a build -d 'Build a target'
+complete -c tool -n 'not __fish_seen_subcommand_from build' -s v -l verbose -d 'Print more'
+complete -c tool -n 'not __fish_seen_subcommand_from build' -l output -r -d 'Where to write'
+complete -c tool -n '__fish_seen_subcommand_from build' -s j -r
+";
+    assert_eq!(completions::fish(&spec()), expected);
+}
@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use krh_args_parser::{parse_with, ArgumentParser, DuplicatePolicy, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    config: Option<PathBuf>,
+    verbose: bool,
+    output: String,
+    include: Vec<String>,
+    warnings: Vec<Cow<'static, str>>,
+}
+
+impl ArgumentParser for Tool {
+    fn config_file(&self) -> Option<PathBuf> {
+        self.config.clone()
+    }
+
+    fn case_insensitive_longs(&self) -> bool {
+        true
+    }
+
+    fn aliases(&self) -> &[(&'static str, &'static str)] {
+        &[("--out", "--output")]
+    }
+
+    fn deprecated(&self) -> &[(&'static str, &'static str)] {
+        &[("--loud", "--verbose")]
+    }
+
+    fn multi_value_flags(&self) -> &[&'static str] {
+        &["--include"]
+    }
+
+    fn required(&self) -> &[&'static str] {
+        &["output"]
+    }
+
+    fn on_duplicate(&self, _flag: &str) -> DuplicatePolicy {
+        DuplicatePolicy::Error
+    }
+
+    fn validate_value(&self, _flag: &str, value: &str) -> Result<(), Cow<'static, str>> {
+        match value {
+            "bad" => Err("not allowed".into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn warn(&mut self, message: Cow<'static, str>) {
+        self.warnings.push(message);
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" | "loud" => self.verbose = true,
+            "output" => self.output = next.take()?,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn multi(&mut self, _flag: &str, values: Vec<String>) -> Result<(), ParseError> {
+        self.include = values;
+        Ok(())
+    }
+}
+
+/// Parse `args` with `contents` as the config file, which is removed afterwards.
+fn parse(name: &str, contents: &str, args: &[&str]) -> (PathBuf, Result<Tool, ParseError>) {
+    let file = format!("krh_args_parser_{name}_{}", std::process::id());
+    let path = std::env::temp_dir().join(file);
+    std::fs::write(&path, contents).unwrap();
+    let tool = Tool {
+        config: Some(path.clone()),
+        ..Tool::default()
+    };
+    let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    let result = parse_with(tool, args.into_iter());
+    std::fs::remove_file(&path).unwrap();
+    (path, result)
+}
+
+#[test]
+fn switches_ignore_their_value() {
+    let (_, tool) = parse("switches", "output = x\nverbose = true\n", &["--include=a"]);
+    assert!(tool.unwrap().verbose);
+}
+
+#[test]
+fn entries_go_through_aliases_and_case_folding() {
+    let (_, tool) = parse("aliases", "OUT = x\n", &["--include=a"]);
+    assert_eq!(tool.unwrap().output, "x");
+}
+
+#[test]
+fn entries_warn_when_deprecated() {
+    let (_, tool) = parse("deprecated", "output = x\nloud = yes\n", &["--include=a"]);
+    let tool = tool.unwrap();
+    assert!(tool.verbose);
+    assert_eq!(tool.warnings, ["'--loud' is deprecated, use '--verbose'"]);
+}
+
+#[test]
+fn entries_collect_multi_value_flags_before_the_command_line() {
+    let (_, tool) = parse("multi", "output = x\ninclude = a\n", &["--include=b"]);
+    assert_eq!(tool.unwrap().include, ["a", "b"]);
+}
+
+#[test]
+fn entries_count_as_required_but_not_as_duplicates() {
+    let (_, tool) = parse("required", "output = x\n", &["--include=a"]);
+    assert_eq!(tool.unwrap().output, "x");
+
+    let (_, tool) = parse("duplicates", "output = x\n", &["--output=y"]);
+    assert_eq!(tool.unwrap().output, "y");
+}
+
+#[test]
+fn entry_errors_keep_the_flag_error() {
+    let (path, result) = parse("invalid", "output = bad\n", &["--include=a"]);
+    let error = result.err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Error in config file '{}' on line 1: Invalid value 'bad' for '--output': not allowed",
+            path.display()
+        )
+    );
+    let ParseError::ConfigEntry { line, error, .. } = error else {
+        panic!("expected a config entry error, got {error:?}");
+    };
+    assert_eq!(line, 1);
+    assert!(
+        matches!(*error, ParseError::InvalidValue { .. }),
+        "{error:?}"
+    );
+}
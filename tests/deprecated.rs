@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    flags: Vec<String>,
+    warnings: Vec<Cow<'static, str>>,
+}
+
+impl ArgumentParser for Tool {
+    fn deprecated(&self) -> &[(&'static str, &'static str)] {
+        &[("--colour", "--color"), ("-c", "--color")]
+    }
+
+    fn warn(&mut self, message: Cow<'static, str>) {
+        self.warnings.push(message);
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        self.flags.push(format!("--{long}"));
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.flags.push(format!("-{short}"));
+        Ok(())
+    }
+}
+
+#[test]
+fn deprecated_longs_warn_and_still_work() {
+    assert_parses::<Tool>(&["--colour"], |tool| {
+        assert_eq!(tool.warnings, ["'--colour' is deprecated, use '--color'"]);
+        assert_eq!(tool.flags, ["--colour"]);
+    });
+}
+
+#[test]
+fn deprecated_shorts_warn_and_still_work() {
+    assert_parses::<Tool>(&["-xc"], |tool| {
+        assert_eq!(tool.warnings, ["'-c' is deprecated, use '--color'"]);
+        assert_eq!(tool.flags, ["-x", "-c"]);
+    });
+}
+
+#[test]
+fn other_flags_do_not_warn() {
+    assert_parses::<Tool>(&["--color", "-x"], |tool| assert!(tool.warnings.is_empty()));
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "derive")]
+
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{parse_partial, ArgumentParser, ParseError};
+
+/// Build things.
+#[derive(Default, ArgumentParser)]
+struct Tool {
+    /// Print more.
+    #[arg(long, short)]
+    verbose: bool,
+    /// Where to write.
+    #[arg(long, short = 'o')]
+    output: Option<String>,
+    /// Search here too.
+    #[arg(long = "include", short = 'I')]
+    includes: Vec<String>,
+    #[arg(long)]
+    jobs: u32,
+    #[arg(positional)]
+    target: Option<String>,
+    #[arg(positional)]
+    inputs: Vec<String>,
+    skipped: bool,
+}
+
+/// Takes one positional, so a second is unexpected.
+#[derive(Default, ArgumentParser)]
+struct Single {
+    #[arg(positional)]
+    path: Option<String>,
+}
+
+#[test]
+fn switches() {
+    assert_parses::<Tool>(&["--jobs", "1"], |tool| assert!(!tool.verbose));
+    assert_parses::<Tool>(&["--verbose"], |tool| assert!(tool.verbose));
+    assert_parses::<Tool>(&["-v"], |tool| assert!(tool.verbose));
+}
+
+#[test]
+fn optional_values() {
+    assert_parses::<Tool>(&["--jobs", "1"], |tool| assert_eq!(tool.output, None));
+    assert_parses::<Tool>(&["--output", "a"], |tool| {
+        assert_eq!(tool.output.as_deref(), Some("a"))
+    });
+    assert_parses::<Tool>(&["-ob"], |tool| {
+        assert_eq!(tool.output.as_deref(), Some("b"))
+    });
+}
+
+#[test]
+fn repeated_values() {
+    assert_parses::<Tool>(&["--include", "a", "-Ib", "-I", "c"], |tool| {
+        assert_eq!(tool.includes, ["a", "b", "c"])
+    });
+}
+
+#[test]
+fn renamed_flags() {
+    assert_error::<Tool>(&["--includes", "a"], "at argument 1: Unrecognized argument");
+    assert_error::<Tool>(&["-i", "a"], "at argument 1: Unrecognized argument");
+}
+
+#[test]
+fn single_values_take_the_last() {
+    assert_parses::<Tool>(&["--jobs", "2", "--jobs=3"], |tool| {
+        assert_eq!(tool.jobs, 3)
+    });
+}
+
+#[test]
+fn positionals_fill_in_field_order() {
+    assert_parses::<Tool>(&["all", "a.c", "--verbose", "b.c"], |tool| {
+        assert_eq!(tool.target.as_deref(), Some("all"));
+        assert_eq!(tool.inputs, ["a.c", "b.c"]);
+        assert!(tool.verbose);
+    });
+}
+
+#[test]
+fn fields_without_arg_are_not_flags() {
+    assert_error::<Tool>(&["--skipped"], "at argument 1: Unrecognized argument");
+    assert_parses::<Tool>(&["--jobs", "1"], |tool| assert!(!tool.skipped));
+}
+
+#[test]
+fn unparsable_values() {
+    assert_error::<Tool>(
+        &["--jobs", "many"],
+        "Invalid value 'many' for '--jobs': invalid digit found in string",
+    );
+    let args = ["--jobs", "many"].map(String::from);
+    let error = krh_args_parser::parse::<Tool>(args.into_iter())
+        .err()
+        .unwrap();
+    let ParseError::At { error, .. } = error else {
+        panic!("{error:?}")
+    };
+    assert!(matches!(
+        *error,
+        ParseError::InvalidValue { flag: Some(flag), value, .. } if flag == "--jobs" && value == "many"
+    ));
+}
+
+#[test]
+fn unknown_flags_are_unhandled() {
+    assert_error::<Tool>(&["-x"], "at argument 1: Unrecognized argument");
+    let args = ["-v", "--other", "x"].map(String::from);
+    let (tool, rest) = parse_partial::<Tool>(args.into_iter()).unwrap();
+    assert!(tool.verbose);
+    assert_eq!(rest, ["--other", "x"]);
+}
+
+#[test]
+fn unexpected_positionals_are_unhandled() {
+    assert_parses::<Single>(&["a"], |single| {
+        assert_eq!(single.path.as_deref(), Some("a"))
+    });
+    assert_error::<Single>(&["a", "b"], "at argument 2: Unrecognized argument");
+
+    let args = ["a", "b", "c"].map(String::from);
+    let (single, rest) = parse_partial::<Single>(args.into_iter()).unwrap();
+    assert_eq!(single.path.as_deref(), Some("a"));
+    assert_eq!(rest, ["b", "c"]);
+}
+
+#[test]
+fn help_lists_the_flags() {
+    let help = Tool::default().help();
+    assert!(help.starts_with("Build things.\n\n"), "{help}");
+    assert!(help.contains("-v, --verbose"), "{help}");
+    assert!(help.contains("Print more."), "{help}");
+    assert!(help.contains("-I, --include <VALUE>"), "{help}");
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "serde")]
+
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::Deserialized;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Color {
+    Auto,
+    Never,
+}
+
+#[derive(serde::Deserialize)]
+struct Config {
+    #[serde(default)]
+    tags: Vec<String>,
+    paths: Option<Vec<String>>,
+    color: Option<Color>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[test]
+fn a_list_given_once_has_one_value() {
+    assert_parses::<Deserialized<Config>>(&["--tags", "a"], |config| {
+        let config = config.into_inner().unwrap();
+        assert_eq!(config.tags, ["a"]);
+        assert_eq!(config.paths, None);
+    });
+    assert_parses::<Deserialized<Config>>(&["--paths=x"], |config| {
+        assert_eq!(config.into_inner().unwrap().paths.unwrap(), ["x"]);
+    });
+}
+
+#[test]
+fn a_list_given_more_than_once() {
+    assert_parses::<Deserialized<Config>>(&["--tags", "a", "--tags", "b"], |config| {
+        assert_eq!(config.into_inner().unwrap().tags, ["a", "b"]);
+    });
+}
+
+#[test]
+fn scalars_and_enums() {
+    assert_parses::<Deserialized<Config>>(&["--color", "never", "--dry-run"], |config| {
+        let config = config.into_inner().unwrap();
+        assert!(matches!(config.color, Some(Color::Never)));
+        assert!(config.dry_run);
+    });
+    assert_parses::<Deserialized<Config>>(&["--color=auto"], |config| {
+        assert!(matches!(
+            config.into_inner().unwrap().color,
+            Some(Color::Auto)
+        ));
+    });
+    assert_error::<Deserialized<Config>>(
+        &["--dry-run", "--dry-run"],
+        "Invalid arguments: invalid type: sequence, expected a boolean",
+    );
+}
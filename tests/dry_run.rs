@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use krh_args_parser::{parse_dry_run, parse_slice, ArgumentParser, Next, ParseError};
+
+/// How many times `--write` has written, standing in for a side effect.
+static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Default)]
+struct Tool {
+    dry_run: bool,
+    output: Option<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn set_dry_run(&mut self, on: bool) {
+        self.dry_run = on;
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "write" => {
+                let output = next.take()?;
+                if output.is_empty() {
+                    return Err("The output must not be empty".into());
+                }
+                if !self.dry_run {
+                    WRITES.fetch_add(1, Ordering::SeqCst);
+                }
+                self.output = Some(output);
+            }
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+fn dry_run(args: &[&str]) -> Result<Tool, ParseError> {
+    let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    parse_dry_run(args.into_iter())
+}
+
+#[test]
+fn dry_runs_validate_without_side_effects() {
+    let tool = dry_run(&["--write", "out.txt"]).unwrap();
+    assert!(tool.dry_run);
+    assert_eq!(tool.output.as_deref(), Some("out.txt"));
+    assert_eq!(WRITES.load(Ordering::SeqCst), 0);
+
+    assert_eq!(
+        dry_run(&["--write="]).unwrap_err().to_string(),
+        "at argument 1: The output must not be empty"
+    );
+    assert_eq!(WRITES.load(Ordering::SeqCst), 0);
+
+    let tool = parse_slice::<Tool>(&["--write", "out.txt"]).unwrap();
+    assert!(!tool.dry_run);
+    assert_eq!(WRITES.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,54 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{parse_partial, ArgumentParser, Next, ParseError};
+
+/// A custom `--version` that stops parsing, and `finish` that always fails.
+#[derive(Default)]
+struct Tool {
+    version: bool,
+    verbose: bool,
+}
+
+impl ArgumentParser for Tool {
+    fn auto_help(&self) -> bool {
+        false
+    }
+
+    fn should_stop(&self) -> bool {
+        self.version
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "version" => self.version = true,
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ParseError> {
+        Err("finish was called".into())
+    }
+}
+
+#[test]
+fn version_stops_before_a_later_invalid_flag() {
+    assert_parses::<Tool>(&["--verbose", "--version", "--invalid"], |tool| {
+        assert!(tool.version);
+        assert!(tool.verbose);
+    });
+}
+
+#[test]
+fn without_stopping_the_parse_carries_on() {
+    assert_error::<Tool>(&["--verbose", "--invalid"], "Unrecognized argument");
+    assert_error::<Tool>(&["--verbose"], "finish was called");
+}
+
+#[test]
+fn partial_parses_return_what_was_left() {
+    let args = ["--version", "--verbose", "x"].map(String::from);
+    let (tool, rest) = parse_partial::<Tool>(args.into_iter()).unwrap();
+    assert!(!tool.verbose);
+    assert_eq!(rest, ["--verbose", "x"]);
+}
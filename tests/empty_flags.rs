@@ -0,0 +1,39 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{parse_slice, ArgumentParser, Next, ParseError};
+
+#[derive(Debug, Default)]
+struct Tool {
+    longs: Vec<String>,
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        self.longs.push(long.into());
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.args.push(arg.into());
+        Ok(false)
+    }
+}
+
+#[test]
+fn a_bare_separator_ends_the_options() {
+    assert_parses::<Tool>(&["--", "--a"], |tool| {
+        assert!(tool.longs.is_empty());
+        assert_eq!(tool.args, ["--a"]);
+    });
+}
+
+#[test]
+fn an_empty_name_with_a_value_is_an_error() {
+    for arg in ["--=", "--=foo"] {
+        let error = parse_slice::<Tool>(&[arg]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("at argument 1: Empty flag name in '{arg}'")
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use krh_args_parser::{parse_with, ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    fallbacks: Vec<(&'static str, &'static str)>,
+    token: Option<String>,
+    verbose: bool,
+}
+
+impl ArgumentParser for Tool {
+    fn allow_no_args(&self) -> bool {
+        true
+    }
+
+    fn env_fallbacks(&self) -> &[(&'static str, &'static str)] {
+        &self.fallbacks
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "token" => self.token = Some(next.take()?),
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+/// Parse `args` with `--token` falling back to `var`, which each test names differently so
+/// they can run in parallel.
+fn parse(var: &'static str, args: &[&str]) -> Tool {
+    let tool = Tool {
+        fallbacks: vec![("token", var)],
+        ..Tool::default()
+    };
+    let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    parse_with(tool, args.into_iter()).unwrap()
+}
+
+#[test]
+fn missing_flags_come_from_the_environment() {
+    std::env::set_var("KRH_ARGS_PARSER_TEST_MISSING", "from-env");
+    let tool = parse("KRH_ARGS_PARSER_TEST_MISSING", &["--verbose"]);
+    assert_eq!(tool.token.as_deref(), Some("from-env"));
+    assert!(tool.verbose);
+}
+
+#[test]
+fn the_command_line_wins() {
+    std::env::set_var("KRH_ARGS_PARSER_TEST_GIVEN", "from-env");
+    let tool = parse("KRH_ARGS_PARSER_TEST_GIVEN", &["--token", "from-args"]);
+    assert_eq!(tool.token.as_deref(), Some("from-args"));
+}
+
+#[test]
+fn unset_variables_leave_the_flag_unset() {
+    std::env::remove_var("KRH_ARGS_PARSER_TEST_UNSET");
+    let tool = parse("KRH_ARGS_PARSER_TEST_UNSET", &[]);
+    assert_eq!(tool.token, None);
+}
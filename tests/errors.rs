@@ -0,0 +1,15 @@
+use std::error::Error;
+
+use krh_args_parser::{parse_slice, ArgumentParser};
+
+#[derive(Debug, Default)]
+struct Tool;
+
+impl ArgumentParser for Tool {}
+
+#[test]
+fn positioned_errors_are_not_repeated_as_their_source() {
+    let error = parse_slice::<Tool>(&["--bad"]).unwrap_err();
+    assert_eq!(error.to_string(), "at argument 1: Unrecognized argument");
+    assert!(error.source().is_none());
+}
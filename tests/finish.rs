@@ -0,0 +1,62 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// Needs `--input`, checked in `finish`, and has a `run` subcommand.
+#[derive(Default)]
+struct Tool {
+    input: Option<String>,
+    command: Option<String>,
+    finished: bool,
+}
+
+impl ArgumentParser for Tool {
+    fn subcommands(&self) -> &[&'static str] {
+        &["run"]
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "input" => self.input = Some(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn subcommand(
+        &mut self,
+        command: &str,
+        _command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<(), ParseError> {
+        self.command = Some(command.into());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ParseError> {
+        if self.input.is_none() {
+            return Err("Missing required flag '--input'".into());
+        }
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn finish_runs_after_every_argument() {
+    assert_parses::<Tool>(&["--input", "a"], |tool| {
+        assert_eq!(tool.input.as_deref(), Some("a"));
+        assert!(tool.finished);
+    });
+}
+
+#[test]
+fn finish_errors_fail_the_parse() {
+    assert_error::<Tool>(&["run"], "Missing required flag '--input'");
+}
+
+#[test]
+fn finish_runs_after_a_subcommand() {
+    assert_parses::<Tool>(&["--input", "a", "run", "x"], |tool| {
+        assert_eq!(tool.command.as_deref(), Some("run"));
+        assert!(tool.finished);
+    });
+}
@@ -0,0 +1,80 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, CommandSpec, DuplicatePolicy, FlagSpec, Next, ParseError};
+
+/// `--output`/`-o` takes a value and `--verbose`/`-v` does not. Only the first of each counts.
+#[derive(Default)]
+struct Tool {
+    output: String,
+    verbose: bool,
+    calls: usize,
+    files: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn on_duplicate(&self, _flag: &str) -> DuplicatePolicy {
+        DuplicatePolicy::FirstWins
+    }
+
+    fn describe(&self) -> CommandSpec {
+        CommandSpec::new("tool")
+            .flag(FlagSpec::long("output").with_short('o').takes_value())
+            .flag(FlagSpec::long("verbose").with_short('v'))
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        self.calls += 1;
+        match long {
+            "output" => self.output = next.take()?,
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'o' => self.long("output", next),
+            'v' => self.long("verbose", next),
+            _ => Err(ParseError::Unhandled),
+        }
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files.push(arg.into());
+        Ok(false)
+    }
+}
+
+#[test]
+fn duplicates_are_not_handled() {
+    let args = ["--output", "a", "--output", "b", "--output=c", "x"];
+    assert_parses::<Tool>(&args, |tool| {
+        assert_eq!(tool.output, "a");
+        assert_eq!(tool.calls, 1);
+        assert_eq!(tool.files, ["x"]);
+    });
+}
+
+#[test]
+fn a_duplicate_switch_leaves_the_following_argument() {
+    assert_parses::<Tool>(&["--verbose", "--verbose", "x"], |tool| {
+        assert_eq!(tool.calls, 1);
+        assert_eq!(tool.files, ["x"]);
+    });
+}
+
+#[test]
+fn duplicate_shorts_skip_their_values() {
+    let args = ["-o", "a", "-ob", "-vo", "c", "-o=d", "-vv", "x"];
+    assert_parses::<Tool>(&args, |tool| {
+        assert_eq!(tool.output, "a");
+        assert!(tool.verbose);
+        assert_eq!(tool.calls, 2);
+        assert_eq!(tool.files, ["x"]);
+    });
+}
@@ -0,0 +1,46 @@
+use krh_args_parser::{default_help, ArgumentParser, CommandSpec, FlagSpec, Next, ParseError};
+
+/// Has a flag but does not override `describe`, so its help has no name.
+#[derive(Default)]
+struct Unnamed;
+
+impl ArgumentParser for Unnamed {
+    fn known_longs(&self) -> &[&'static str] {
+        &["verbose"]
+    }
+
+    fn long(&mut self, _long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+/// Knows of nothing.
+#[derive(Default)]
+struct Empty;
+
+impl ArgumentParser for Empty {}
+
+#[test]
+fn usage_without_a_name() {
+    assert_eq!(
+        Unnamed.help(),
+        "Usage: [OPTIONS]\n\nOptions:\n      --verbose"
+    );
+    assert_eq!(Empty.help(), "");
+}
+
+#[test]
+fn non_ascii_names_align() {
+    let spec = CommandSpec::new("tool")
+        .flag(FlagSpec::long("größe").help("Size"))
+        .flag(FlagSpec::long("count").help("Count"));
+    assert_eq!(
+        default_help(&spec),
+        "\
+Usage: tool [OPTIONS]
+
+Options:
+      --größe  Size
+      --count  Count"
+    );
+}
@@ -0,0 +1,59 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Make {
+    vars: Vec<(String, String)>,
+    targets: Vec<String>,
+}
+
+impl ArgumentParser for Make {
+    fn positional_keyvals(&self) -> bool {
+        true
+    }
+
+    fn key_value(&mut self, key: &str, value: &str) -> Result<(), ParseError> {
+        self.vars.push((key.into(), value.into()));
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.targets.push(arg.into());
+        Ok(false)
+    }
+}
+
+fn var(key: &str, value: &str) -> (String, String) {
+    (key.into(), value.into())
+}
+
+#[test]
+fn key_values_are_split_from_targets() {
+    assert_parses::<Make>(&["VAR=1", "all", "CC="], |make| {
+        assert_eq!(make.vars, [var("VAR", "1"), var("CC", "")]);
+        assert_eq!(make.targets, ["all"]);
+    });
+}
+
+#[test]
+fn only_the_first_equals_splits() {
+    assert_parses::<Make>(&["VAR=a=b"], |make| {
+        assert_eq!(make.vars, [var("VAR", "a=b")])
+    });
+}
+
+#[test]
+fn a_leading_equals_stays_positional() {
+    assert_parses::<Make>(&["=x"], |make| {
+        assert!(make.vars.is_empty());
+        assert_eq!(make.targets, ["=x"]);
+    });
+}
+
+#[test]
+fn arguments_after_a_separator_stay_positional() {
+    assert_parses::<Make>(&["--", "VAR=1"], |make| {
+        assert!(make.vars.is_empty());
+        assert_eq!(make.targets, ["VAR=1"]);
+    });
+}
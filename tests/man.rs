@@ -0,0 +1,49 @@
+use krh_args_parser::{man_page, CommandSpec, FlagSpec};
+
+#[test]
+fn man_page_escapes_dashes_backslashes_and_requests() {
+    let spec = CommandSpec::new("my-tool")
+        .about("Build things")
+        .flag(
+            FlagSpec::long("dry-run")
+                .with_short('n')
+                .help(".Show what would be built"),
+        )
+        .flag(
+            FlagSpec::long("output")
+                .takes_value()
+                .help("'Where' to write\n.so evil"),
+        )
+        .subcommand(
+            CommandSpec::new("build")
+                .about("Build a target, see C:\\docs")
+                .flag(FlagSpec::short('j').takes_value().with_default("1")),
+        );
+
+    let expected = r#".TH MY\-TOOL 1
+.SH NAME
+my\-tool \- Build things
+.SH SYNOPSIS
+.B my\-tool
+[\fIOPTIONS\fR]
+[\fICOMMAND\fR]
+.SH OPTIONS
+.TP
+\fB\-n\fR, \fB\-\-dry\-run\fR
+\&.Show what would be built
+.TP
+\fB\-\-output\fR \fIVALUE\fR
+\&'Where' to write
+\&.so evil
+.SH COMMANDS
+.TP
+\fBbuild\fR
+Build a target, see C:\edocs
+.RS
+.TP
+\fB\-j\fR \fIVALUE\fR
+(default: 1)
+.RE
+"#;
+    assert_eq!(man_page(&spec), expected);
+}
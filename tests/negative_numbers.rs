@@ -0,0 +1,83 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Seek {
+    offset: Option<String>,
+    verbose: bool,
+    positions: Vec<String>,
+}
+
+impl ArgumentParser for Seek {
+    fn allow_negative_numbers(&self) -> bool {
+        true
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "offset" => self.offset = Some(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'v' => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.positions.push(arg.into());
+        Ok(false)
+    }
+}
+
+/// The same, without negative numbers.
+#[derive(Default)]
+struct Strict(Seek);
+
+impl ArgumentParser for Strict {
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        self.0.long(long, next)
+    }
+}
+
+#[test]
+fn negative_numbers_are_values() {
+    for number in ["-5", "-5.0", "-5e3", "-.5", "-5E-3"] {
+        assert_parses::<Seek>(&["--offset", number], |seek| {
+            assert_eq!(seek.offset.as_deref(), Some(number))
+        });
+    }
+}
+
+#[test]
+fn negative_numbers_are_positionals() {
+    assert_parses::<Seek>(&["-5", "-5.0", "-5e3"], |seek| {
+        assert_eq!(seek.positions, ["-5", "-5.0", "-5e3"]);
+        assert!(!seek.verbose);
+    });
+}
+
+#[test]
+fn flags_are_still_flags() {
+    assert_parses::<Seek>(&["-v", "-3"], |seek| {
+        assert!(seek.verbose);
+        assert_eq!(seek.positions, ["-3"]);
+    });
+    assert_error::<Seek>(&["--offset", "-v"], "Expected value, got flag -v");
+    assert_error::<Seek>(&["--offset", "-5e"], "Expected value, got flag -5e");
+}
+
+#[test]
+fn negative_numbers_are_flags_by_default() {
+    assert_error::<Strict>(&["--offset", "-5"], "Expected value, got flag -5");
+}
@@ -0,0 +1,41 @@
+use krh_args_parser::{parse, parse_slice, ArgumentParser, ParseError};
+
+/// Like `git status`, which is useful with no arguments.
+#[derive(Debug, Default)]
+struct Status {
+    finished: bool,
+}
+
+impl ArgumentParser for Status {
+    fn allow_no_args(&self) -> bool {
+        true
+    }
+
+    fn finish(&mut self) -> Result<(), ParseError> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Tool;
+
+impl ArgumentParser for Tool {}
+
+#[test]
+fn an_empty_iterator_finishes_the_default_parser() {
+    let status = parse::<Status>(std::iter::empty()).unwrap();
+    assert!(status.finished);
+}
+
+#[test]
+fn no_arguments_fail_by_default() {
+    assert_eq!(
+        parse::<Tool>(std::iter::empty()).unwrap_err(),
+        ParseError::NoArguments
+    );
+    assert_eq!(
+        parse_slice::<Tool>(&[]).unwrap_err().to_string(),
+        "No arguments given"
+    );
+}
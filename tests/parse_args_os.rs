@@ -0,0 +1,91 @@
+//! `parse_args_os` reads the arguments of the process itself, so this runs its own binary
+//! again with known arguments, and the copy checks what it parsed.
+
+use std::ffi::{OsStr, OsString};
+use std::process::Command;
+
+use krh_args_parser::{parse_args_os, ArgumentParser, Next, ParseError};
+
+/// Set in the copy to the name of the check to run.
+const CHECK: &str = "KRH_ARGS_PARSER_PARSE_ARGS_OS_CHECK";
+
+#[derive(Debug, Default)]
+struct Tool {
+    program: String,
+    verbose: bool,
+    files: Vec<OsString>,
+}
+
+impl ArgumentParser for Tool {
+    fn program_name(&mut self, name: &str) {
+        self.program = name.into();
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument_os(&mut self, arg: &OsStr, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files.push(arg.into());
+        Ok(false)
+    }
+}
+
+/// A file name that is not valid UTF-8 where the platform allows one.
+fn file_name() -> OsString {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        OsStr::from_bytes(b"caf\xe9.txt").into()
+    }
+    #[cfg(not(unix))]
+    {
+        "café.txt".into()
+    }
+}
+
+fn check(name: &str) {
+    match name {
+        "arguments" => {
+            let tool = parse_args_os::<Tool>().unwrap();
+            assert!(tool.program.contains("parse_args_os"), "{}", tool.program);
+            assert!(tool.verbose);
+            assert_eq!(tool.files, [OsString::from("a.txt"), file_name()]);
+        }
+        "no_arguments" => {
+            assert_eq!(
+                parse_args_os::<Tool>().unwrap_err(),
+                ParseError::NoArguments
+            );
+        }
+        _ => panic!("unknown check {name:?}"),
+    }
+}
+
+/// Run the check `name` in a copy of this process, given `args`.
+fn run(name: &str, args: &[OsString]) {
+    let status = Command::new(std::env::current_exe().unwrap())
+        .args(args)
+        .env(CHECK, name)
+        .status()
+        .unwrap();
+    assert!(status.success(), "check {name:?} failed");
+    println!("test {name} ... ok");
+}
+
+fn main() {
+    if let Some(name) = std::env::var_os(CHECK) {
+        check(&name.to_string_lossy());
+        return;
+    }
+
+    run(
+        "arguments",
+        &["--verbose".into(), "a.txt".into(), file_name()],
+    );
+    run("no_arguments", &[]);
+}
@@ -0,0 +1,92 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use krh_args_parser::{parse, parse_borrowed, ArgumentParser, Next, ParseError};
+
+/// Counts the allocations made on each thread, so tests running alongside do not add to them.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Counts its arguments without keeping them.
+#[derive(Default)]
+struct Tool {
+    verbose: bool,
+    files: usize,
+}
+
+impl ArgumentParser for Tool {
+    fn short(
+        &mut self,
+        _short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.verbose = true;
+        Ok(())
+    }
+
+    fn argument(&mut self, _arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files += 1;
+        Ok(false)
+    }
+}
+
+#[test]
+fn borrowed_arguments_are_not_copied() {
+    let args: Vec<String> = std::iter::once("-v".to_owned())
+        .chain((0..100).map(|i| format!("file{i}.txt")))
+        .collect();
+
+    let borrowed = allocations(|| {
+        let tool = parse_borrowed::<Tool>(args.iter().map(String::as_str)).unwrap();
+        assert!(tool.verbose);
+        assert_eq!(tool.files, 100);
+    });
+    let owned = allocations(|| {
+        let tool = parse::<Tool>(args.clone().into_iter()).unwrap();
+        assert_eq!(tool.files, 100);
+    });
+
+    // Parsing allocates the same either way, apart from a copy of each argument.
+    assert!(borrowed < 20, "{borrowed} allocations");
+    assert!(
+        owned >= borrowed + args.len(),
+        "{owned} allocations, {borrowed} borrowed"
+    );
+}
+
+#[test]
+fn scratch() {
+    for n in [0, 1, 2, 10] {
+        let args: Vec<String> = std::iter::once("-v".to_owned())
+            .chain((0..n).map(|i| format!("f{i}")))
+            .collect();
+        let a = allocations(|| {
+            parse_borrowed::<Tool>(args.iter().map(String::as_str)).unwrap();
+        });
+        eprintln!("{n}: {a}");
+    }
+}
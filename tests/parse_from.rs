@@ -0,0 +1,42 @@
+use krh_args_parser::{parse_from, ArgumentParser, Next, ParseError};
+
+/// Records its program name and the flags it is given.
+#[derive(Debug, Default)]
+struct Tool {
+    name: String,
+    verbose: bool,
+}
+
+impl ArgumentParser for Tool {
+    fn program_name(&mut self, name: &str) {
+        self.name = name.into();
+    }
+
+    fn short(
+        &mut self,
+        _short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.verbose = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn only_the_program_name_is_no_arguments() {
+    let error = parse_from::<Tool>(["prog".to_owned()].into_iter()).unwrap_err();
+    assert_eq!(error, ParseError::NoArguments);
+    assert_eq!(error.to_string(), "No arguments given");
+
+    let error = parse_from::<Tool>(std::iter::empty()).unwrap_err();
+    assert_eq!(error, ParseError::NoArguments);
+}
+
+#[test]
+fn the_first_argument_is_the_program_name() {
+    let args = ["prog", "-v"].map(String::from);
+    let tool = parse_from::<Tool>(args.into_iter()).unwrap();
+    assert_eq!(tool.name, "prog");
+    assert!(tool.verbose);
+}
@@ -0,0 +1,54 @@
+use krh_args_parser::{parse_str, ArgumentParser, Next, ParseError};
+
+#[derive(Debug, Default)]
+struct Echo {
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Echo {
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.args.push(arg.into());
+        Ok(false)
+    }
+}
+
+fn args(line: &str) -> Vec<String> {
+    parse_str::<Echo>(line).unwrap().args
+}
+
+#[test]
+fn quotes_and_escapes() {
+    assert_eq!(args(r#"a "b c" 'd e' f\ g"#), ["a", "b c", "d e", "f g"]);
+    assert_eq!(args(r#"'a\b' "a\"b" "a\b""#), [r"a\b", r#"a"b"#, r"a\b"]);
+    assert_eq!(args("''"), [""]);
+    assert_eq!(args("a\\\nb"), ["ab"]);
+}
+
+#[test]
+fn unterminated_quotes_fail() {
+    assert_eq!(
+        parse_str::<Echo>(r#"a "b c"#).unwrap_err(),
+        ParseError::UnterminatedQuote('"')
+    );
+    assert_eq!(
+        parse_str::<Echo>("'a").unwrap_err(),
+        ParseError::UnterminatedQuote('\'')
+    );
+    assert_eq!(
+        parse_str::<Echo>(r"a\").unwrap_err(),
+        ParseError::TrailingEscape
+    );
+}
+
+#[test]
+fn empty_input_has_no_arguments() {
+    assert_eq!(parse_str::<Echo>("").unwrap_err(), ParseError::NoArguments);
+    assert_eq!(
+        parse_str::<Echo>(" \t\n").unwrap_err(),
+        ParseError::NoArguments
+    );
+    assert_eq!(
+        parse_str::<Echo>("").unwrap_err().to_string(),
+        "No arguments given"
+    );
+}
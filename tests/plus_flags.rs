@@ -0,0 +1,62 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// Like `set`, where `+x` turns an option on and `-x` turns it off.
+#[derive(Default)]
+struct Set {
+    changes: Vec<(char, bool)>,
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Set {
+    fn allow_plus_flags(&self) -> bool {
+        true
+    }
+
+    fn plus(&mut self, flag: char, _is_last: bool, _next: &mut dyn Next) -> Result<(), ParseError> {
+        self.changes.push((flag, true));
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        self.changes.push((short, false));
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.args.push(arg.into());
+        Ok(false)
+    }
+}
+
+#[test]
+fn plus_flags() {
+    assert_parses::<Set>(&["+x"], |set| assert_eq!(set.changes, [('x', true)]));
+}
+
+#[test]
+fn plus_clusters() {
+    assert_parses::<Set>(&["+xv"], |set| {
+        assert_eq!(set.changes, [('x', true), ('v', true)])
+    });
+}
+
+#[test]
+fn plus_and_minus_flags_mix() {
+    assert_parses::<Set>(&["-a", "+b"], |set| {
+        assert_eq!(set.changes, [('a', false), ('b', true)])
+    });
+}
+
+#[test]
+fn a_lone_plus_is_positional() {
+    assert_parses::<Set>(&["+"], |set| {
+        assert!(set.changes.is_empty());
+        assert_eq!(set.args, ["+"]);
+    });
+}
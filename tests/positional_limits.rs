@@ -0,0 +1,109 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// Takes one or two files, and `exec` anywhere hands the rest to a subcommand.
+#[derive(Default)]
+struct Tool {
+    files: Vec<String>,
+    command: Option<(String, Vec<String>)>,
+}
+
+impl ArgumentParser for Tool {
+    fn min_positionals(&self) -> usize {
+        1
+    }
+
+    fn max_positionals(&self) -> Option<usize> {
+        Some(2)
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        if arg == "exec" {
+            return Ok(true);
+        }
+        self.files.push(arg.into());
+        Ok(false)
+    }
+
+    fn subcommand(
+        &mut self,
+        command: &str,
+        command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<(), ParseError> {
+        self.command = Some((command.into(), command_args.collect()));
+        Ok(())
+    }
+}
+
+/// Takes no positionals, only the `run` subcommand or one started by `argument`.
+#[derive(Default)]
+struct Runner {
+    command: Option<String>,
+}
+
+impl ArgumentParser for Runner {
+    fn max_positionals(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn subcommands(&self) -> &[&'static str] {
+        &["run"]
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        Ok(arg == "exec")
+    }
+
+    fn subcommand(
+        &mut self,
+        command: &str,
+        _command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<(), ParseError> {
+        self.command = Some(command.into());
+        Ok(())
+    }
+}
+
+#[test]
+fn under_the_minimum() {
+    assert_error::<Tool>(&["exec", "x"], "Too few arguments");
+}
+
+#[test]
+fn within_the_limits() {
+    assert_parses::<Tool>(&["a"], |tool| assert_eq!(tool.files, ["a"]));
+    assert_parses::<Tool>(&["a", "b"], |tool| assert_eq!(tool.files, ["a", "b"]));
+}
+
+#[test]
+fn over_the_maximum() {
+    assert_error::<Tool>(
+        &["a", "b", "c"],
+        "at argument 3: Too many arguments (expected at most 2)",
+    );
+}
+
+#[test]
+fn subcommands_do_not_count() {
+    assert_parses::<Tool>(&["a", "b", "exec", "x", "y"], |tool| {
+        assert_eq!(tool.files, ["a", "b"]);
+        assert_eq!(
+            tool.command,
+            Some(("exec".into(), vec!["x".into(), "y".into()]))
+        );
+    });
+}
+
+#[test]
+fn no_positionals_still_allows_subcommands() {
+    assert_parses::<Runner>(&["run", "x"], |runner| {
+        assert_eq!(runner.command.as_deref(), Some("run"))
+    });
+    assert_parses::<Runner>(&["exec", "x"], |runner| {
+        assert_eq!(runner.command.as_deref(), Some("exec"))
+    });
+    assert_error::<Runner>(
+        &["x"],
+        "at argument 1: Too many arguments (expected at most 0)",
+    );
+}
@@ -0,0 +1,27 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{parse_partial, Positionals};
+
+#[test]
+fn flags_are_unhandled() {
+    assert_error::<Positionals>(&["a", "--force"], "at argument 2: Unrecognized argument");
+    assert_error::<Positionals>(&["-f"], "at argument 1: Unrecognized argument");
+
+    let args = ["a", "--force", "b"].map(String::from);
+    let (Positionals(args), rest) = parse_partial::<Positionals>(args.into_iter()).unwrap();
+    assert_eq!(args, ["a"]);
+    assert_eq!(rest, ["--force", "b"]);
+}
+
+#[test]
+fn ignored_flags_are_skipped() {
+    assert_parses::<Positionals<true>>(&["-f", "a", "--force", "b", "-xy"], |Positionals(args)| {
+        assert_eq!(args, ["a", "b"])
+    });
+}
+
+#[test]
+fn ignored_flags_with_attached_values_are_collected() {
+    assert_parses::<Positionals<true>>(&["--force=x", "-f=y", "a"], |Positionals(args)| {
+        assert_eq!(args, ["--force=x", "-f=y", "a"])
+    });
+}
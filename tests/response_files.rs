@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Linker {
+    verbose: bool,
+    inputs: Vec<String>,
+}
+
+impl ArgumentParser for Linker {
+    fn allow_response_files(&self) -> bool {
+        true
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.inputs.push(arg.into());
+        Ok(false)
+    }
+}
+
+/// A response file unique to this process, removed when dropped.
+struct ResponseFile(PathBuf);
+
+impl ResponseFile {
+    fn new(name: &str, contents: &str) -> Self {
+        let file = ResponseFile(
+            std::env::temp_dir().join(format!("krh_args_parser_{name}_{}.rsp", std::process::id())),
+        );
+        std::fs::write(&file.0, contents).unwrap();
+        file
+    }
+
+    /// The argument that expands this file.
+    fn arg(&self) -> String {
+        format!("@{}", self.0.display())
+    }
+}
+
+impl Drop for ResponseFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn response_files_are_spliced_in_place() {
+    let path = ResponseFile::new("splice", "b.o --verbose\nc.o\n");
+    assert_parses::<Linker>(&["a.o", &path.arg(), "d.o"], |linker| {
+        assert!(linker.verbose);
+        assert_eq!(linker.inputs, ["a.o", "b.o", "c.o", "d.o"]);
+    });
+}
+
+#[test]
+fn response_files_may_be_nested() {
+    let inner = ResponseFile::new("inner", "c.o");
+    let outer = ResponseFile::new("outer", &format!("b.o {} d.o", inner.arg()));
+    assert_parses::<Linker>(&["a.o", &outer.arg()], |linker| {
+        assert_eq!(linker.inputs, ["a.o", "b.o", "c.o", "d.o"]);
+    });
+}
+
+#[test]
+fn nesting_is_limited() {
+    // A file that includes itself.
+    let path = ResponseFile::new("recursive", "");
+    std::fs::write(&path.0, path.arg()).unwrap();
+    assert_error::<Linker>(&[&path.arg()], "response files are nested too deeply");
+}
+
+#[test]
+fn a_doubled_at_is_a_literal() {
+    assert_parses::<Linker>(&["@@name"], |linker| assert_eq!(linker.inputs, ["@name"]));
+}
+
+#[test]
+fn nothing_after_a_separator_is_expanded() {
+    let path = ResponseFile::new("separator", "--verbose");
+    assert_parses::<Linker>(&["--", &path.arg()], |linker| {
+        assert!(!linker.verbose);
+        assert_eq!(linker.inputs, [path.arg()]);
+    });
+
+    let path = ResponseFile::new("separator_inside", "a.o -- @b");
+    assert_parses::<Linker>(&[&path.arg(), "@c"], |linker| {
+        assert_eq!(linker.inputs, ["a.o", "@b", "@c"]);
+    });
+}
+
+#[test]
+fn missing_response_files_fail() {
+    assert_error::<Linker>(
+        &["@/nonexistent/krh_args_parser.rsp"],
+        "Could not read response file '/nonexistent/krh_args_parser.rsp'",
+    );
+}
@@ -0,0 +1,73 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{parse_lenient, parse_slice, ArgumentParser, Next, ParseError};
+
+#[derive(Debug, Default)]
+struct Tool {
+    verbosity: usize,
+    quiet: bool,
+}
+
+impl ArgumentParser for Tool {
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'v' => self.verbosity += 1,
+            'q' => self.quiet = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+/// The same, with clusters of at most four flags.
+#[derive(Debug, Default)]
+struct Limited(Tool);
+
+impl ArgumentParser for Limited {
+    fn max_short_chain(&self) -> usize {
+        4
+    }
+
+    fn short(&mut self, short: char, is_last: bool, next: &mut dyn Next) -> Result<(), ParseError> {
+        self.0.short(short, is_last, next)
+    }
+}
+
+#[test]
+fn clusters_up_to_the_limit() {
+    assert_parses::<Limited>(&["-vvvq"], |tool| {
+        assert_eq!(tool.0.verbosity, 3);
+        assert!(tool.0.quiet);
+    });
+}
+
+#[test]
+fn longer_clusters_fail() {
+    assert_error::<Limited>(
+        &["-vvvvq"],
+        "at argument 1: Too many flags in one short flag cluster (at most 4)",
+    );
+    assert_error::<Limited>(
+        &["-qqqqq"],
+        "at argument 1: Too many flags in one short flag cluster (at most 4)",
+    );
+}
+
+#[test]
+fn flags_past_the_limit_are_never_handled() {
+    let args = ["-vqvqv"].map(String::from);
+    let (tool, result) = parse_lenient::<Limited>(args.into_iter());
+    assert!(result.is_err());
+    assert_eq!(tool.0.verbosity, 2);
+}
+
+#[test]
+fn long_clusters_are_unbounded_by_default() {
+    let cluster = format!("-{}", "v".repeat(1_000_000));
+    let tool = parse_slice::<Tool>(&[&cluster]).unwrap();
+    assert_eq!(tool.verbosity, 1_000_000);
+}
@@ -0,0 +1,75 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, LongPrefixMode, Next, ParseError};
+
+/// Takes find-style `-name PATTERN` and java-style `-jar FILE` flags, and `-v`.
+#[derive(Default)]
+struct Tool {
+    name: Option<String>,
+    jar: Option<String>,
+    verbose: bool,
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn long_prefix_mode(&self) -> LongPrefixMode {
+        LongPrefixMode::SingleDash
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "name" => self.name = Some(next.take()?),
+            "jar" => self.jar = Some(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'v' => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.args.push(arg.into());
+        Ok(false)
+    }
+}
+
+#[test]
+fn find_style() {
+    assert_parses::<Tool>(&[".", "-name", "*.rs"], |tool| {
+        assert_eq!(tool.name.as_deref(), Some("*.rs"));
+        assert_eq!(tool.args, ["."]);
+    });
+}
+
+#[test]
+fn java_style() {
+    assert_parses::<Tool>(&["-jar", "app.jar", "arg"], |tool| {
+        assert_eq!(tool.jar.as_deref(), Some("app.jar"));
+        assert_eq!(tool.args, ["arg"]);
+    });
+}
+
+#[test]
+fn a_single_character_is_still_short() {
+    assert_parses::<Tool>(&["-v", "-jar=app.jar"], |tool| {
+        assert!(tool.verbose);
+        assert_eq!(tool.jar.as_deref(), Some("app.jar"));
+    });
+}
+
+#[test]
+fn double_dashes_still_work() {
+    assert_parses::<Tool>(&["--name", "x"], |tool| {
+        assert_eq!(tool.name.as_deref(), Some("x"))
+    });
+}
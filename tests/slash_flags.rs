@@ -0,0 +1,61 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{parse_with, ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    slash_flags: bool,
+    verbose: bool,
+    output: Option<String>,
+    files: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn allow_slash_flags(&self) -> bool {
+        self.slash_flags
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            "o" => self.output = Some(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files.push(arg.into());
+        Ok(false)
+    }
+}
+
+fn with_slash_flags(args: &[&str]) -> Tool {
+    let tool = Tool {
+        slash_flags: true,
+        ..Tool::default()
+    };
+    let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    parse_with(tool, args.into_iter()).unwrap()
+}
+
+#[test]
+fn slash_flags_are_long_flags() {
+    let tool = with_slash_flags(&["/verbose", "/o:file"]);
+    assert!(tool.verbose);
+    assert_eq!(tool.output.as_deref(), Some("file"));
+    assert!(tool.files.is_empty());
+}
+
+#[test]
+fn paths_stay_positional() {
+    let tool = with_slash_flags(&["/path/to/file", "/"]);
+    assert_eq!(tool.files, ["/path/to/file", "/"]);
+}
+
+#[test]
+fn slash_flags_are_off_by_default() {
+    assert_parses::<Tool>(&["/o:file", "/path/to/file"], |tool| {
+        assert_eq!(tool.output, None);
+        assert_eq!(tool.files, ["/o:file", "/path/to/file"]);
+    });
+}
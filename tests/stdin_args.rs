@@ -0,0 +1,85 @@
+use std::io::{Cursor, Read};
+
+use krh_args_parser::{parse_with, ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Tool {
+    input: String,
+    verbose: bool,
+    output: Option<String>,
+    files: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn stdin_args_flag(&self) -> Option<&'static str> {
+        Some("args-from-stdin")
+    }
+
+    fn stdin_reader(&mut self) -> Box<dyn Read> {
+        Box::new(Cursor::new(std::mem::take(&mut self.input)))
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            "output" => self.output = Some(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files.push(arg.into());
+        Ok(false)
+    }
+}
+
+/// Parse `args` with `input` on standard input.
+fn parse(input: &str, args: &[&str]) -> Result<Tool, ParseError> {
+    let tool = Tool {
+        input: input.into(),
+        ..Tool::default()
+    };
+    let args: Vec<String> = args.iter().map(|&arg| arg.into()).collect();
+    parse_with(tool, args.into_iter())
+}
+
+#[test]
+fn stdin_arguments_come_before_the_rest() {
+    let tool = parse(
+        "--verbose b.txt\nc.txt\n",
+        &["a.txt", "--args-from-stdin", "d.txt"],
+    )
+    .unwrap();
+    assert!(tool.verbose);
+    assert_eq!(tool.files, ["a.txt", "b.txt", "c.txt", "d.txt"]);
+}
+
+#[test]
+fn stdin_arguments_are_split_like_a_command_line() {
+    let tool = parse(
+        r#"--output "my file.txt" 'a b' c\ d"#,
+        &["--args-from-stdin"],
+    )
+    .unwrap();
+    assert_eq!(tool.output.as_deref(), Some("my file.txt"));
+    assert_eq!(tool.files, ["a b", "c d"]);
+}
+
+#[test]
+fn a_flag_from_stdin_can_take_a_value_after_the_sentinel() {
+    let tool = parse("--output", &["--args-from-stdin", "x"]).unwrap();
+    assert_eq!(tool.output.as_deref(), Some("x"));
+}
+
+#[test]
+fn empty_stdin_adds_nothing() {
+    let tool = parse("", &["--args-from-stdin", "a.txt"]).unwrap();
+    assert_eq!(tool.files, ["a.txt"]);
+}
+
+#[test]
+fn unbalanced_quotes_on_stdin_fail() {
+    let error = parse("'a", &["--args-from-stdin"]).err().unwrap();
+    assert_eq!(error.to_string(), "at argument 1: Unterminated ' quote");
+}
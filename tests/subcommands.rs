@@ -0,0 +1,130 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{parse_subcommand, ArgumentParser, Next, ParseError};
+
+/// `git [--verbose] remote [-v] add [-f] <name> <url>`, one parser per level.
+#[derive(Default)]
+struct Git {
+    verbose: bool,
+    remote: Option<Remote>,
+}
+
+#[derive(Default)]
+struct Remote {
+    verbose: bool,
+    add: Option<Add>,
+}
+
+#[derive(Default)]
+struct Add {
+    fetch: bool,
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Git {
+    fn subcommands(&self) -> &[&'static str] {
+        &["remote"]
+    }
+
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn subcommand(
+        &mut self,
+        _command: &str,
+        command_args: Box<dyn Iterator<Item = String>>,
+    ) -> Result<(), ParseError> {
+        let (mut remote, rest) = parse_subcommand::<Remote>(command_args)?;
+        let mut rest = rest.peekable();
+        if rest.next_if(|arg| arg == "add").is_some() {
+            let (add, mut rest) = parse_subcommand::<Add>(Box::new(rest))?;
+            if let Some(arg) = rest.next() {
+                return Err(format!("Unexpected argument '{arg}'").into());
+            }
+            remote.add = Some(add);
+        }
+        self.remote = Some(remote);
+        Ok(())
+    }
+}
+
+impl ArgumentParser for Remote {
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'v' => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+impl ArgumentParser for Add {
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'f' => self.fetch = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.args.push(arg.into());
+        Ok(false)
+    }
+}
+
+#[test]
+fn each_level_parses_its_own_flags() {
+    let args = ["--verbose", "remote", "-v", "add", "-f", "origin", "url"];
+    assert_parses::<Git>(&args, |git| {
+        assert!(git.verbose);
+        let remote = git.remote.unwrap();
+        assert!(remote.verbose);
+        let add = remote.add.unwrap();
+        assert!(add.fetch);
+        assert_eq!(add.args, ["origin", "url"]);
+    });
+}
+
+#[test]
+fn a_level_returns_the_arguments_it_declines() {
+    let args = ["-v", "add", "-f", "origin"].map(String::from);
+    let (remote, rest) = parse_subcommand::<Remote>(Box::new(args.into_iter())).unwrap();
+    assert!(remote.verbose);
+    assert_eq!(rest.collect::<Vec<_>>(), ["add", "-f", "origin"]);
+
+    let (remote, rest) = parse_subcommand::<Remote>(Box::new(std::iter::empty())).unwrap();
+    assert!(!remote.verbose);
+    assert_eq!(rest.count(), 0);
+}
+
+#[test]
+fn the_deepest_level_leaves_nothing_declined() {
+    assert_error::<Git>(
+        &["remote", "add", "-x", "origin"],
+        "Unexpected argument '-x'",
+    );
+}
+
+#[test]
+fn a_subcommand_may_have_no_subcommand() {
+    assert_parses::<Git>(&["remote", "-v"], |git| {
+        let remote = git.remote.unwrap();
+        assert!(remote.verbose);
+        assert!(remote.add.is_none());
+    });
+}
@@ -0,0 +1,51 @@
+use krh_args_parser::testing::assert_parses;
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// `cp [-v] SRC... DEST`, where the first positional takes every one after it.
+#[derive(Default)]
+struct Cp {
+    verbose: bool,
+    paths: Vec<String>,
+}
+
+impl ArgumentParser for Cp {
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'v' => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.paths.push(arg.into());
+        self.paths.extend(next.take_all());
+        Ok(false)
+    }
+}
+
+#[test]
+fn take_all_collects_up_to_the_next_flag() {
+    assert_parses::<Cp>(&["a", "b", "c", "-v", "d"], |copy| {
+        assert!(copy.verbose);
+        assert_eq!(copy.paths, ["a", "b", "c", "d"]);
+    });
+}
+
+#[test]
+fn flags_do_not_stop_collection_after_a_separator() {
+    assert_parses::<Cp>(&["-v", "--", "a", "-b", "c"], |copy| {
+        assert!(copy.verbose);
+        assert_eq!(copy.paths, ["a", "-b", "c"]);
+    });
+}
+
+#[test]
+fn take_all_may_take_nothing() {
+    assert_parses::<Cp>(&["a", "-v"], |copy| assert_eq!(copy.paths, ["a"]));
+}
@@ -0,0 +1,52 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+#[derive(Default)]
+struct Plot {
+    points: Vec<(String, String)>,
+    verbose: bool,
+}
+
+impl ArgumentParser for Plot {
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "point" => {
+                let [x, y]: [String; 2] = next.take_n(2)?.try_into().unwrap();
+                self.points.push((x, y));
+            }
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn point_takes_two_values() {
+    assert_parses::<Plot>(&["--point", "1", "2", "--point", "3", "4"], |plot| {
+        assert_eq!(
+            plot.points,
+            [("1".into(), "2".into()), ("3".into(), "4".into())]
+        );
+    });
+}
+
+#[test]
+fn too_few_values_name_the_flag() {
+    assert_error::<Plot>(
+        &["--point", "1"],
+        "at argument 1: Flag '--point' expected 2 values but got 1",
+    );
+    assert_error::<Plot>(
+        &["--point", "1", "--verbose"],
+        "at argument 1: Flag '--point' expected 2 values but got 1",
+    );
+}
+
+#[test]
+fn an_attached_value_is_only_one() {
+    assert_error::<Plot>(
+        &["--point=1", "2"],
+        "at argument 1: Flag '--point' expected 2 values but got 1",
+    );
+}
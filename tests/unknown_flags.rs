@@ -0,0 +1,81 @@
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// A wrapper that handles `--verbose` and `-q`, and passes every other flag on.
+#[derive(Default)]
+struct Wrapper {
+    verbose: bool,
+    quiet: bool,
+    passed_on: Vec<String>,
+    args: Vec<String>,
+}
+
+impl ArgumentParser for Wrapper {
+    fn long(&mut self, long: &str, _next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "verbose" => self.verbose = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn short(
+        &mut self,
+        short: char,
+        _is_last: bool,
+        _next: &mut dyn Next,
+    ) -> Result<(), ParseError> {
+        match short {
+            'q' => self.quiet = true,
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn unknown_long(&mut self, flag: &str) -> Result<(), ParseError> {
+        self.passed_on.push(flag.into());
+        Ok(())
+    }
+
+    fn unknown_short(&mut self, short: char) -> Result<(), ParseError> {
+        self.passed_on.push(format!("-{short}"));
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.args.push(arg.into());
+        Ok(false)
+    }
+}
+
+/// Handles nothing, so unknown flags keep failing.
+#[derive(Default)]
+struct Nothing;
+
+impl ArgumentParser for Nothing {}
+
+#[test]
+fn unknown_flags_are_collected() {
+    let args = ["--verbose", "--color=always", "-qx", "--jobs", "4"];
+    assert_parses::<Wrapper>(&args, |wrapper| {
+        assert!(wrapper.verbose);
+        assert!(wrapper.quiet);
+        assert_eq!(wrapper.passed_on, ["--color=always", "-x", "--jobs"]);
+        // A value in the following argument is a positional.
+        assert_eq!(wrapper.args, ["4"]);
+    });
+}
+
+#[test]
+fn repeated_unknown_shorts_are_each_collected() {
+    assert_parses::<Wrapper>(&["-xxq"], |wrapper| {
+        assert_eq!(wrapper.passed_on, ["-x", "-x"]);
+        assert!(wrapper.quiet);
+    });
+}
+
+#[test]
+fn unknown_flags_fail_by_default() {
+    assert_error::<Nothing>(&["--color"], "at argument 1: Unrecognized argument");
+    assert_error::<Nothing>(&["-x"], "at argument 1: Unrecognized argument");
+}
@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+
+use krh_args_parser::testing::{assert_error, assert_parses};
+use krh_args_parser::{ArgumentParser, Next, ParseError};
+
+/// `--all` takes every value up to the next flag, `--rest` every argument left, and
+/// `--quiet` none.
+#[derive(Default)]
+struct Tool {
+    all: Vec<String>,
+    rest: Vec<String>,
+    files: Vec<String>,
+}
+
+impl ArgumentParser for Tool {
+    fn validate_value(&self, _flag: &str, value: &str) -> Result<(), Cow<'static, str>> {
+        match value {
+            "bad" => Err("not allowed".into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "all" => self.all = next.take_all(),
+            "rest" => self.rest.extend(next.rest()),
+            "quiet" => {}
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+
+    fn argument(&mut self, arg: &str, _next: &mut dyn Next) -> Result<bool, ParseError> {
+        self.files.push(arg.into());
+        Ok(false)
+    }
+}
+
+#[test]
+fn take_all_checks_every_value() {
+    assert_parses::<Tool>(&["--all", "a", "b"], |tool| {
+        assert_eq!(tool.all, ["a", "b"])
+    });
+    assert_error::<Tool>(
+        &["--all", "a", "bad"],
+        "Invalid value 'bad' for '--all': not allowed",
+    );
+}
+
+#[test]
+fn rejected_take_all_values_never_reach_the_handler() {
+    let args = ["--all", "a", "bad"].map(String::from);
+    let (tool, result) = krh_args_parser::parse_lenient::<Tool>(args.into_iter());
+    assert!(tool.all.is_empty());
+    assert!(result.is_err());
+}
+
+#[test]
+fn rest_stops_before_a_rejected_value() {
+    assert_parses::<Tool>(&["--rest", "a", "-b"], |tool| {
+        assert_eq!(tool.rest, ["a", "-b"])
+    });
+
+    let args = ["--rest", "a", "bad", "c"].map(String::from);
+    let (tool, result) = krh_args_parser::parse_lenient::<Tool>(args.into_iter());
+    assert_eq!(tool.rest, ["a"]);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "at argument 1: Invalid value 'bad' for '--rest': not allowed"
+    );
+}
+
+/// `--max` is checked against the `--mode` given before it.
+#[derive(Default)]
+struct Limits {
+    mode: String,
+    max: Vec<String>,
+}
+
+impl ArgumentParser for Limits {
+    fn validate_value(&self, flag: &str, value: &str) -> Result<(), Cow<'static, str>> {
+        let limit = if self.mode == "small" { 10 } else { 1000 };
+        match flag {
+            "--max" if !value.parse::<u32>().is_ok_and(|max| max <= limit) => {
+                Err(format!("must be at most {limit}").into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn long(&mut self, long: &str, next: &mut dyn Next) -> Result<(), ParseError> {
+        match long {
+            "mode" => self.mode = next.take()?,
+            "max" => self.max.push(next.take()?),
+            _ => return Err(ParseError::Unhandled),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn validation_sees_earlier_flags() {
+    assert_parses::<Limits>(&["--max", "500"], |limits| assert_eq!(limits.max, ["500"]));
+    assert_error::<Limits>(
+        &["--mode", "small", "--max", "500"],
+        "Invalid value '500' for '--max': must be at most 10",
+    );
+
+    let args = ["--max", "5", "--mode=small", "--max=50"].map(String::from);
+    let (limits, result) = krh_args_parser::parse_lenient::<Limits>(args.into_iter());
+    assert_eq!(limits.max, ["5"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn rest_past_a_flag_is_checked_once_the_handler_returns() {
+    assert_parses::<Tool>(&["--rest", "a", "-b", "c"], |tool| {
+        assert_eq!(tool.rest, ["a", "-b", "c"])
+    });
+    assert_error::<Tool>(
+        &["--rest", "a", "-b", "bad"],
+        "Invalid value 'bad' for '--rest': not allowed",
+    );
+}
+
+#[test]
+fn arguments_left_by_a_switch_are_not_rejected() {
+    assert_parses::<Tool>(&["--quiet", "bad"], |tool| assert_eq!(tool.files, ["bad"]));
+    assert_error::<Tool>(&["--all", "bad"], "Invalid value 'bad' for '--all'");
+}